@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::types::{Event, State};
+
+/// A numeric cvar animated between keyframes, under `[[animations]]` in the config file.
+///
+/// ```toml
+/// # Cycle the HUD colour, the default tick command also changes cl_hud_color so it must be
+/// # changed to stop the two fighting
+/// tick_command = "clan"
+///
+/// [[animations]]
+/// cvar = "cl_hud_color"
+/// keyframes = [{ time = 0, value = 0 }, { time = 3000, value = 5 }]
+/// repeat = true
+///
+/// # Flash the crosshair red when the damage report shows damage was taken
+/// [[animations]]
+/// cvar = "cl_crosshaircolor_r"
+/// on = "damage_taken"
+/// easing = "ease_out"
+/// keyframes = [{ time = 0, value = 255 }, { time = 1000, value = 50 }]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnimationConfig {
+    pub cvar: String,
+    /// At least one keyframe, in order of time
+    pub keyframes: Vec<Keyframe>,
+    #[serde(default)]
+    pub easing: Easing,
+    /// Start again from the first keyframe after the last one
+    #[serde(default)]
+    pub repeat: bool,
+    /// Play when this happens instead of from the start, reactions take priority over other
+    /// animations of the same cvar while they play
+    pub on: Option<AnimationTrigger>,
+    /// Decimal places sent to the console, cvars like `cl_hud_color` only accept integers
+    #[serde(default)]
+    pub decimals: usize,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Keyframe {
+    /// Milliseconds since the animation started
+    pub time: u64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Hold each keyframe's value until the next one
+    Step,
+}
+
+impl Easing {
+    /// Map progress between two keyframes, `t` is from 0 to 1.
+    pub fn apply(&self, t: f64) -> f64 {
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => t * (2.0 - t),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    -1.0 + (4.0 - 2.0 * t) * t
+                }
+            }
+            Self::Step => 0.0,
+        }
+    }
+}
+
+/// The console does not print individual hits, damage is only known from the damage report
+/// printed when the local player dies or the round ends.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnimationTrigger {
+    /// A damage report with damage taken, played once per report
+    DamageTaken,
+    /// A damage report with damage given, played once per report
+    DamageGiven,
+    RoundStart,
+    /// The local player got a kill, needs the player name from a damage report
+    Kill,
+    /// The local player died, needs the player name from a damage report
+    Death,
+}
+
+impl AnimationTrigger {
+    fn matches(&self, event: &Event, state: &State) -> bool {
+        let player = state.player_name.as_deref();
+        match (self, event) {
+            (Self::DamageTaken, Event::DamageReport(report)) => !report.taken.is_empty(),
+            (Self::DamageGiven, Event::DamageReport(report)) => !report.given.is_empty(),
+            (Self::RoundStart, Event::EnterBuyPeriod) => true,
+            (Self::Kill, Event::Kill { attacker, .. }) => Some(attacker.as_str()) == player,
+            (Self::Death, Event::Kill { victim, .. }) => Some(victim.as_str()) == player,
+            _ => false,
+        }
+    }
+}
+
+impl AnimationConfig {
+    fn duration(&self) -> Duration {
+        Duration::from_millis(self.keyframes.last().map_or(0, |keyframe| keyframe.time))
+    }
+
+    /// The value `elapsed` into the animation, held at the last keyframe once it ends.
+    pub fn value_at(&self, elapsed: Duration) -> f64 {
+        let time = elapsed.as_millis() as u64;
+        let next = match self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time)
+        {
+            Some(next) => next,
+            None => return self.keyframes.last().map_or(0.0, |keyframe| keyframe.value),
+        };
+        if next == 0 {
+            return self.keyframes[0].value;
+        }
+        let (from, to) = (self.keyframes[next - 1], self.keyframes[next]);
+        let t = (time - from.time) as f64 / (to.time - from.time) as f64;
+        from.value + (to.value - from.value) * self.easing.apply(t)
+    }
+}
+
+struct Animation {
+    config: AnimationConfig,
+    started: Option<Instant>,
+}
+
+impl Animation {
+    /// The value to show now, or `None` if the animation is not playing.
+    fn value(&mut self, now: Instant) -> Option<f64> {
+        let started = self.started?;
+        let mut elapsed = now.duration_since(started);
+        let duration = self.config.duration();
+        if elapsed > duration {
+            if self.config.repeat && !duration.is_zero() {
+                elapsed =
+                    Duration::from_millis((elapsed.as_millis() % duration.as_millis()) as u64);
+            } else if self.config.on.is_some() {
+                // Reactions show their last value once more then hand back to other animations
+                self.started = None;
+            }
+        }
+        Some(self.config.value_at(elapsed))
+    }
+}
+
+/// Drives every configured animation from the tick loop.
+pub struct CvarAnimator {
+    animations: Vec<Animation>,
+    /// The last value sent for each cvar so unchanged values are not sent again
+    sent: HashMap<String, String>,
+}
+
+impl CvarAnimator {
+    pub fn new(configs: &[AnimationConfig]) -> Self {
+        let animations = configs
+            .iter()
+            .filter(|config| {
+                if config.keyframes.is_empty() {
+                    log::warn!("Skipping animation of {} without keyframes", config.cvar);
+                }
+                !config.keyframes.is_empty()
+            })
+            .map(|config| {
+                let mut config = config.clone();
+                config.keyframes.sort_by_key(|keyframe| keyframe.time);
+                Animation {
+                    config,
+                    started: None,
+                }
+            })
+            .collect();
+        Self {
+            animations,
+            sent: HashMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.animations.is_empty()
+    }
+
+    /// Start any reactions to `event`, `state` should already have been updated with it.
+    pub fn event(&mut self, event: &Event, state: &State, now: Instant) {
+        if *event == Event::Connected {
+            // The game may have restarted with different values
+            self.sent.clear();
+        }
+        for animation in &mut self.animations {
+            if let Some(trigger) = animation.config.on {
+                if trigger.matches(event, state) {
+                    animation.started = Some(now);
+                }
+            }
+        }
+    }
+
+    /// The commands that set every cvar whose value has changed.
+    pub fn tick(&mut self, now: Instant) -> Vec<String> {
+        let mut values: Vec<(String, String)> = Vec::new();
+        // Reactions come after the animations they interrupt so their values win
+        let (reactions, others): (Vec<_>, Vec<_>) = self
+            .animations
+            .iter_mut()
+            .partition(|animation| animation.config.on.is_some());
+        for animation in others.into_iter().chain(reactions) {
+            if animation.config.on.is_none() && animation.started.is_none() {
+                animation.started = Some(now);
+            }
+            if let Some(value) = animation.value(now) {
+                let value = format!("{:.*}", animation.config.decimals, value);
+                let cvar = &animation.config.cvar;
+                match values.iter_mut().find(|(name, _)| name == cvar) {
+                    Some(entry) => entry.1 = value,
+                    None => values.push((cvar.clone(), value)),
+                }
+            }
+        }
+
+        let mut commands = Vec::new();
+        for (cvar, value) in values {
+            if self.sent.get(&cvar) != Some(&value) {
+                commands.push(format!("{} {}", cvar, value));
+                self.sent.insert(cvar, value);
+            }
+        }
+        commands
+    }
+}
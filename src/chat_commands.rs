@@ -0,0 +1,259 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use crate::listener::{Context, Listener};
+use crate::types::{ChatScope, Event, State, Status};
+use crate::writer::sanitize_argument;
+
+/// How many of our own replies to remember so they are not handled as commands
+const SENT_HISTORY: usize = 16;
+
+/// Settings for chat commands, under `[chat_commands]` in the config file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChatCommandConfig {
+    /// Messages starting with this are treated as commands
+    pub prefix: String,
+    /// Minimum time between uses of the same command in milliseconds
+    pub cooldown: u64,
+    /// Names or SteamIDs allowed to use commands, everyone is allowed if empty
+    pub allowlist: Vec<String>,
+}
+
+impl Default for ChatCommandConfig {
+    fn default() -> Self {
+        Self {
+            prefix: String::from("!"),
+            cooldown: 5000,
+            allowlist: Vec::new(),
+        }
+    }
+}
+
+/// A chat command being handled.
+pub struct Invocation<'a> {
+    pub sender: &'a str,
+    /// The sender's SteamID, if they were in the last `status` output
+    pub steam_id: Option<&'a str>,
+    /// Words following the command name
+    pub args: Vec<&'a str>,
+    pub scope: ChatScope,
+    pub state: &'a State,
+}
+
+/// Handles a command and returns the message to reply with, if any. The context can be used to
+/// run other console commands.
+pub type Handler = Box<dyn Fn(&Invocation, &Context) -> Option<String> + Send + Sync>;
+
+pub struct ChatCommand {
+    handler: Handler,
+    cooldown: Duration,
+    allowlist: Vec<String>,
+    last_used: Option<Instant>,
+}
+
+impl ChatCommand {
+    pub fn new(handler: Handler) -> Self {
+        Self {
+            handler,
+            cooldown: Duration::ZERO,
+            allowlist: Vec::new(),
+            last_used: None,
+        }
+    }
+
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Only allow the given names or SteamIDs to use this command.
+    pub fn allowlist(mut self, allowlist: Vec<String>) -> Self {
+        self.allowlist = allowlist;
+        self
+    }
+
+    fn allowed(&self, sender: &str, steam_id: Option<&str>) -> bool {
+        self.allowlist.is_empty()
+            || self
+                .allowlist
+                .iter()
+                .any(|allowed| allowed == sender || Some(allowed.as_str()) == steam_id)
+    }
+}
+
+/// Routes chat messages starting with a prefix (e.g. `!adr`) to handlers, replying with `say`
+/// or `say_team` depending on where the command was sent.
+pub struct ChatCommandRouter {
+    prefix: String,
+    commands: HashMap<String, ChatCommand>,
+    state: State,
+    sent: VecDeque<String>,
+}
+
+impl ChatCommandRouter {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            commands: HashMap::new(),
+            state: State::default(),
+            sent: VecDeque::new(),
+        }
+    }
+
+    /// A router with the built in commands, using the cooldown and allowlist from `config`.
+    pub fn from_config(config: &ChatCommandConfig) -> Self {
+        let cooldown = Duration::from_millis(config.cooldown);
+        let mut router = Self::new(config.prefix.clone());
+        for (name, handler) in builtin_commands() {
+            router = router.command(
+                name,
+                ChatCommand::new(handler)
+                    .cooldown(cooldown)
+                    .allowlist(config.allowlist.clone()),
+            );
+        }
+        router
+    }
+
+    pub fn command(mut self, name: impl Into<String>, command: ChatCommand) -> Self {
+        self.commands.insert(name.into(), command);
+        self
+    }
+
+    fn handle(
+        &mut self,
+        ctx: &Context,
+        sender: &str,
+        text: &str,
+        scope: &ChatScope,
+    ) -> Option<String> {
+        // Our own replies show up as chat too
+        if self.sent.iter().any(|sent| sent == text) {
+            return None;
+        }
+
+        let mut words = text.strip_prefix(&self.prefix)?.split_whitespace();
+        let name = words.next()?.to_lowercase();
+        let command = self.commands.get_mut(&name)?;
+
+        let steam_id = match &self.state.status {
+            Status::Connected(data) => data
+                .player_list
+                .iter()
+                .find(|player| player.name == sender)
+                .map(|player| player.steam_id.as_str()),
+            Status::NotConnected => None,
+        };
+        if !command.allowed(sender, steam_id) {
+            log::debug!("{:?} is not allowed to use {:?}", sender, name);
+            return None;
+        }
+
+        if let Some(last_used) = command.last_used {
+            if last_used.elapsed() < command.cooldown {
+                log::debug!("{:?} is on cooldown", name);
+                return None;
+            }
+        }
+        command.last_used = Some(Instant::now());
+
+        let invocation = Invocation {
+            sender,
+            steam_id,
+            args: words.collect(),
+            scope: scope.clone(),
+            state: &self.state,
+        };
+        (command.handler)(&invocation, ctx)
+    }
+
+    /// Make `reply` safe to put in a `say` command, it must not run other commands or trigger
+    /// another chat command.
+    fn sanitize(&self, reply: &str) -> String {
+        let reply = sanitize_argument(reply);
+        let reply = reply.trim();
+        match reply.strip_prefix(&self.prefix) {
+            Some(reply) => reply.trim_start().to_string(),
+            None => reply.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Listener for ChatCommandRouter {
+    async fn on_event(&mut self, ctx: &Context, event: &Event) {
+        if let Event::Chat {
+            sender,
+            text,
+            scope,
+            ..
+        } = event
+        {
+            if let Some(reply) = self.handle(ctx, sender, text, scope) {
+                let reply = self.sanitize(&reply);
+                if reply.is_empty() {
+                    return;
+                }
+                let say = match scope {
+                    ChatScope::All => "say",
+                    ChatScope::Team => "say_team",
+                };
+                if let Err(e) = ctx.queue(format!("{} \"{}\"", say, reply)) {
+                    log::error!("Error replying to chat command {}", e);
+                }
+                self.sent.push_back(reply);
+                if self.sent.len() > SENT_HISTORY {
+                    self.sent.pop_front();
+                }
+            }
+        }
+    }
+
+    async fn on_state_change(&mut self, _ctx: &Context, state: &State) {
+        self.state = state.clone();
+    }
+}
+
+fn builtin_commands() -> Vec<(&'static str, Handler)> {
+    vec![
+        (
+            "adr",
+            Box::new(|invocation, _| {
+                let state = invocation.state;
+                Some(if !state.rounds.is_empty() {
+                    format!("{:.0} ADR over {} rounds", state.adr(), state.rounds.len())
+                } else {
+                    format!("{} damage", state.total_damage_given)
+                })
+            }),
+        ),
+        (
+            "score",
+            Box::new(|invocation, _| {
+                let state = invocation.state;
+                Some(format!(
+                    "Round {} on {}, {} damage given, {} taken",
+                    state.round,
+                    state.map.as_deref().unwrap_or("no map"),
+                    state.total_damage_given,
+                    state.total_damage_taken
+                ))
+            }),
+        ),
+        (
+            "pause",
+            Box::new(|_, ctx| match ctx.queue("mp_pause_match") {
+                Ok(()) => Some(String::from("Pausing at the end of the round")),
+                Err(e) => {
+                    log::error!("Error pausing match {}", e);
+                    None
+                }
+            }),
+        ),
+    ]
+}
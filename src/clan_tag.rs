@@ -0,0 +1,147 @@
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::constants::WRITE_SETTLE_TIME;
+use crate::types::State;
+use crate::writer::sanitize_argument;
+
+/// The game cuts clan tags off after this many characters
+pub const MAX_CLAN_TAG_LENGTH: usize = 15;
+
+/// Settings for the clan tag animation, under `[clan_tag]` in the config file. Nothing is sent
+/// unless there are `frames` or a `marquee`.
+///
+/// ```toml
+/// [clan_tag]
+/// frames = ["R{round}", "{adr} ADR"]
+/// marquee = "playing {map}"
+/// width = 8
+/// interval = 1000
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClanTagConfig {
+    /// Frames shown in order, `{round}`, `{adr}`, `{damage}`, `{map}` and `{mode}` are replaced
+    /// with values from the state
+    pub frames: Vec<String>,
+    /// Text scrolled through a window of `width` characters after the frames, filled in like
+    /// the frames before it is scrolled
+    pub marquee: Option<String>,
+    pub width: usize,
+    /// Time each frame is shown in milliseconds, frames change at most once per tick
+    pub interval: u64,
+    /// Command that sets the clan tag, `{frame}` is replaced with the frame
+    pub command: String,
+}
+
+impl Default for ClanTagConfig {
+    fn default() -> Self {
+        Self {
+            frames: Vec::new(),
+            marquee: None,
+            width: 10,
+            interval: 1000,
+            command: String::from("clan \"{frame}\""),
+        }
+    }
+}
+
+/// Cycles through clan tag frames as it is ticked.
+pub struct ClanTagAnimator {
+    frames: Vec<String>,
+    marquee: Option<String>,
+    width: usize,
+    interval: Duration,
+    command: String,
+    index: usize,
+    last_change: Option<Instant>,
+    last_sent: Option<String>,
+}
+
+impl ClanTagAnimator {
+    pub fn new(config: &ClanTagConfig) -> Self {
+        Self {
+            frames: config.frames.clone(),
+            marquee: config.marquee.clone(),
+            width: config.width,
+            // Every write waits for the console to settle, so faster frames would only queue up
+            interval: Duration::from_millis(config.interval).max(WRITE_SETTLE_TIME * 2),
+            command: config.command.clone(),
+            index: 0,
+            last_change: None,
+            last_sent: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty() && self.marquee.is_none()
+    }
+
+    /// Every frame filled in from `state`, the marquee is split after it is filled in as its
+    /// length depends on the state.
+    fn render(&self, state: &State) -> Vec<String> {
+        let mut frames: Vec<String> = self
+            .frames
+            .iter()
+            .map(|frame| render_frame(frame, state))
+            .collect();
+        if let Some(text) = &self.marquee {
+            frames.extend(marquee(&fill_template(text, state), self.width));
+        }
+        frames
+    }
+
+    /// The command to send this tick, if the frame is due and has changed since it was last sent.
+    pub fn tick(&mut self, state: &State, now: Instant) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        match self.last_change {
+            Some(last_change) if now.duration_since(last_change) < self.interval => return None,
+            Some(_) => self.index += 1,
+            None => {}
+        }
+        self.last_change = Some(now);
+
+        let frames = self.render(state);
+        self.index %= frames.len();
+        let frame = frames[self.index].clone();
+        if self.last_sent.as_ref() == Some(&frame) {
+            return None;
+        }
+        self.last_sent = Some(frame.clone());
+        Some(self.command.replace("{frame}", &frame))
+    }
+}
+
+/// Every window of `width` characters of `text` scrolling from right to left.
+pub fn marquee(text: &str, width: usize) -> Vec<String> {
+    let width = width.clamp(1, MAX_CLAN_TAG_LENGTH);
+    let padding = " ".repeat(width);
+    let chars: Vec<char> = format!("{}{}{}", padding, text, padding).chars().collect();
+    chars
+        .windows(width)
+        .map(|window| window.iter().collect())
+        .take(chars.len() - width)
+        .collect()
+}
+
+/// Fill in the state values and make the frame safe to quote in a command.
+pub fn render_frame(template: &str, state: &State) -> String {
+    fill_template(template, state)
+        .chars()
+        .take(MAX_CLAN_TAG_LENGTH)
+        .collect()
+}
+
+fn fill_template(template: &str, state: &State) -> String {
+    let mode = state.game_mode.to_string();
+    let text = template
+        .replace("{round}", &state.round.to_string())
+        .replace("{adr}", &format!("{:.0}", state.adr()))
+        .replace("{damage}", &state.total_damage_given.to_string())
+        .replace("{map}", state.map.as_deref().unwrap_or(""))
+        .replace("{mode}", &mode);
+    sanitize_argument(&text)
+}
@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "sqlite")]
+use clap::{Args as ClapArgs, ValueEnum};
+use clap::{Parser, Subcommand};
+use regex::Regex;
+
+#[cfg(feature = "sqlite")]
+use csgo_netcon::storage::MatchFilter;
+
+use csgo_netcon::config::Config;
+use csgo_netcon::types::GenericResult;
+
+const DEFAULT_CONFIG_PATH: &str = "netcontool.toml";
+
+#[derive(Debug, Parser)]
+#[command(name = "netcontool", version, about)]
+pub struct Args {
+    /// Path to a TOML config file [default: netcontool.toml if it exists]
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+    /// Host the game is running on
+    #[arg(long)]
+    pub host: Option<String>,
+    /// Port passed to the game with -netconport
+    #[arg(short, long)]
+    pub port: Option<u16>,
+    /// Time between ticks in milliseconds
+    #[arg(long)]
+    pub tick_interval: Option<u64>,
+    /// Command sent every tick while enabled and in game
+    #[arg(long)]
+    pub tick_command: Option<String>,
+    /// Time between status queries while in game in milliseconds, 0 to disable
+    #[arg(long)]
+    pub status_interval: Option<u64>,
+    /// State listener to register, can be given multiple times [default: discord with the rpc feature]
+    #[arg(long = "listener")]
+    pub listeners: Vec<String>,
+    /// Log filter, e.g. info or csgo_netcon=debug
+    #[arg(long)]
+    pub log_level: Option<String>,
+    /// Print events and state changes to stdout as JSON lines
+    #[arg(long)]
+    pub json: bool,
+    /// Record every console line to a file that can be replayed later
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+    /// Path of the SQLite database used by the sqlite listener and history commands
+    #[arg(long, value_name = "FILE")]
+    pub database: Option<PathBuf>,
+    /// TOML file of automation rules
+    #[arg(long, value_name = "FILE")]
+    pub rules: Option<PathBuf>,
+    /// Rhai script to run, can be given multiple times
+    #[arg(long = "script", value_name = "FILE")]
+    pub scripts: Vec<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Replay a file written with --record instead of connecting to the game
+    Replay {
+        file: PathBuf,
+        /// Replay as fast as possible instead of at the recorded speed
+        #[arg(long)]
+        fast: bool,
+    },
+    /// Send a command and exit, with status 1 if it could not be sent or 2 if --wait timed out
+    Send {
+        /// Commands to send, separated by ;
+        command: String,
+        /// Wait for a line of output matching this regex and print it
+        #[arg(long, value_name = "PATTERN")]
+        wait: Option<Regex>,
+        /// Seconds to wait for matching output
+        #[arg(long, default_value_t = 5)]
+        timeout: u64,
+    },
+    /// Type commands at the console with history and completion
+    #[cfg(feature = "repl")]
+    Shell {
+        /// File to keep command history in
+        #[arg(long, value_name = "FILE", default_value = ".netcontool_history")]
+        history: PathBuf,
+    },
+    /// List past matches recorded by the sqlite listener
+    #[cfg(feature = "sqlite")]
+    History {
+        #[command(flatten)]
+        filter: HistoryFilter,
+        /// Number of matches to list
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+    /// Show totals and averages over past matches
+    #[cfg(feature = "sqlite")]
+    Stats {
+        #[command(flatten)]
+        filter: HistoryFilter,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Debug, ClapArgs)]
+pub struct HistoryFilter {
+    /// Only matches on this map, e.g. de_mirage
+    #[arg(long)]
+    pub map: Option<String>,
+    /// Only matches in this game mode, e.g. competitive
+    #[arg(long)]
+    pub mode: Option<String>,
+}
+
+#[cfg(feature = "sqlite")]
+impl HistoryFilter {
+    pub fn to_filter(&self, limit: Option<usize>) -> MatchFilter {
+        MatchFilter {
+            map: self.map.clone(),
+            mode: self.mode.clone(),
+            limit,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+impl Args {
+    /// Load the config file and apply any flags on top of it.
+    pub fn config(&self) -> GenericResult<Config> {
+        let mut config = match &self.config {
+            Some(path) => Config::load(path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Config::load(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Config::default(),
+        };
+
+        if let Some(host) = &self.host {
+            config.host = host.clone();
+        }
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(tick_interval) = self.tick_interval {
+            config.tick_interval = tick_interval;
+        }
+        if let Some(tick_command) = &self.tick_command {
+            config.tick_command = tick_command.clone();
+        }
+        if let Some(status_interval) = self.status_interval {
+            config.status_interval = status_interval;
+        }
+        if !self.listeners.is_empty() {
+            config.listeners = self.listeners.clone();
+        }
+        if let Some(log_level) = &self.log_level {
+            config.log_level = log_level.clone();
+        }
+        if self.json {
+            config.json = true;
+        }
+        if let Some(database) = &self.database {
+            config.database = database.clone();
+        }
+        if let Some(rules) = &self.rules {
+            config.rules_file = Some(rules.clone());
+        }
+        if !self.scripts.is_empty() {
+            config.scripts = self.scripts.clone();
+        }
+
+        Ok(config)
+    }
+}
@@ -0,0 +1,274 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+
+use crate::constants::{
+    LINE_CHANNEL_SIZE, QUERY_TIMEOUT, RECONNECT_MAX, RECONNECT_MIN, RESYNC_COMMAND,
+    WRITE_SETTLE_TIME,
+};
+use crate::reader::LineReader;
+use crate::stream_reader::stream_reader;
+use crate::types::{Event, GenericResult, StatusData};
+use crate::writer::{CommandError, CommandWriter};
+
+static QUERY_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Options for [`NetconClient::connect_with`] and [`NetconClient::connect_retry_with`].
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    /// Send raw console lines to this channel instead of a new one, so receivers subscribed
+    /// before connecting see the first lines of the session
+    pub lines: Option<broadcast::Sender<String>>,
+    /// Query `status`, `game_type` and `game_mode` after every connect, one-shot clients that
+    /// do not track state can turn this off
+    pub resync: bool,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            lines: None,
+            resync: true,
+        }
+    }
+}
+
+/// A connection to the CS:GO console opened with `-netconport`.
+///
+/// Console output is parsed in a background task and made available as a stream of [`Event`]s.
+/// If the connection drops (e.g. the game restarts) the client emits [`Event::Disconnected`] and
+/// keeps trying to reconnect with an increasing backoff, emitting [`Event::Connected`] and
+/// re-querying `status`, `game_type` and `game_mode` once it succeeds.
+///
+/// Commands are sent through a [`CommandWriter`] so writes never overlap.
+pub struct NetconClient {
+    addr: SocketAddr,
+    tx: async_channel::Sender<Event>,
+    rx: async_channel::Receiver<Event>,
+    lines: broadcast::Sender<String>,
+    writer: CommandWriter,
+    supervisor: JoinHandle<()>,
+}
+
+impl NetconClient {
+    /// Connect to the netcon port at `addr`.
+    pub async fn connect(addr: SocketAddr) -> tokio::io::Result<Self> {
+        Self::connect_with(addr, ClientOptions::default()).await
+    }
+
+    /// Same as [`NetconClient::connect`] with custom options.
+    pub async fn connect_with(addr: SocketAddr, options: ClientOptions) -> tokio::io::Result<Self> {
+        let stream = TcpStream::connect(&addr).await?;
+        Ok(Self::from_stream(addr, stream, options))
+    }
+
+    /// Connect to the netcon port at `addr`, retrying every `interval` until the game is listening.
+    pub async fn connect_retry(addr: SocketAddr, interval: Duration) -> Self {
+        Self::connect_retry_with(addr, interval, ClientOptions::default()).await
+    }
+
+    /// Same as [`NetconClient::connect_retry`] with custom options.
+    pub async fn connect_retry_with(
+        addr: SocketAddr,
+        interval: Duration,
+        options: ClientOptions,
+    ) -> Self {
+        loop {
+            if let Ok(stream) = TcpStream::connect(&addr).await {
+                return Self::from_stream(addr, stream, options);
+            }
+            sleep(interval).await;
+        }
+    }
+
+    fn from_stream(addr: SocketAddr, stream: TcpStream, options: ClientOptions) -> Self {
+        let (tx, rx) = async_channel::unbounded();
+        let lines = options
+            .lines
+            .unwrap_or_else(|| broadcast::channel(LINE_CHANNEL_SIZE).0);
+        let writer = CommandWriter::spawn(addr, lines.subscribe(), WRITE_SETTLE_TIME);
+        let supervisor = tokio::spawn(supervise(
+            addr,
+            stream,
+            tx.clone(),
+            lines.clone(),
+            writer.clone(),
+            options.resync,
+        ));
+
+        Self {
+            addr,
+            tx,
+            rx,
+            lines,
+            writer,
+            supervisor,
+        }
+    }
+
+    /// The address of the netcon port this client is connected to.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// A stream of events parsed from the console.
+    ///
+    /// Events are distributed between receivers, each event is only received once.
+    pub fn events(&self) -> async_channel::Receiver<Event> {
+        self.rx.clone()
+    }
+
+    /// A sender that can be used to inject events (e.g. [`Event::Tick`]) into the event stream.
+    pub fn event_sender(&self) -> async_channel::Sender<Event> {
+        self.tx.clone()
+    }
+
+    /// Wait for the next event from the console.
+    pub async fn next_event(&self) -> Option<Event> {
+        self.rx.recv().await.ok()
+    }
+
+    /// Send a command to the console and wait until it has been written.
+    pub async fn send_command(&self, command: &str) -> Result<(), CommandError> {
+        self.writer.send(command).await
+    }
+
+    /// Every raw line read from the console, including lines that were not parsed into events.
+    pub fn lines(&self) -> broadcast::Receiver<String> {
+        self.lines.subscribe()
+    }
+
+    /// A handle to the command writer that can be moved into other tasks.
+    pub fn writer(&self) -> CommandWriter {
+        self.writer.clone()
+    }
+
+    /// Send a command and return the console output it produced.
+    ///
+    /// The command is wrapped in `echo` commands printing unique sentinels, every line printed
+    /// between the sentinels is returned. Fails with [`CommandError::Timeout`] if the closing
+    /// sentinel is not seen within [`QUERY_TIMEOUT`], or [`CommandError::Lagged`] if output was
+    /// missed.
+    pub async fn query(&self, command: &str) -> Result<Vec<String>, CommandError> {
+        self.query_timeout(command, QUERY_TIMEOUT).await
+    }
+
+    /// Same as [`NetconClient::query`] with a custom timeout.
+    pub async fn query_timeout(
+        &self,
+        command: &str,
+        duration: Duration,
+    ) -> Result<Vec<String>, CommandError> {
+        let id = QUERY_ID.fetch_add(1, Ordering::Relaxed);
+        let start = format!("netcon-query-{}-start", id);
+        let end = format!("netcon-query-{}-end", id);
+
+        // Subscribe before sending so no output can be missed
+        let mut lines = self.lines.subscribe();
+        self.writer
+            .send(format!("echo {};{};echo {}", start, command.trim(), end))
+            .await?;
+
+        timeout(duration, async {
+            let mut output = None;
+            loop {
+                let line = match lines.recv().await {
+                    Ok(line) => line,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        return Err(CommandError::Lagged(skipped))
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Err(CommandError::Closed),
+                };
+                let line = line.trim();
+                if line == start {
+                    output = Some(Vec::new());
+                } else if line == end {
+                    if let Some(output) = output {
+                        return Ok(output);
+                    }
+                } else if let Some(output) = &mut output {
+                    output.push(line.to_string());
+                }
+            }
+        })
+        .await
+        .map_err(|_| CommandError::Timeout)?
+    }
+
+    /// Query and parse the output of `status`.
+    pub async fn status(&self) -> GenericResult<StatusData> {
+        let output = self.query("status").await?;
+        let mut output = output.into_iter();
+        let hostname = output
+            .by_ref()
+            .find_map(|line| line.strip_prefix("hostname: ").map(|h| h.to_string()))
+            .ok_or("Status output did not contain a hostname")?;
+
+        let rest: String = output.map(|line| line + "\n").collect();
+        let mut reader = LineReader::new(rest.as_bytes());
+        StatusData::parse(hostname, &mut reader).await
+    }
+}
+
+impl Drop for NetconClient {
+    fn drop(&mut self) {
+        self.supervisor.abort();
+    }
+}
+
+/// Read from the console until the connection drops, then reconnect and resynchronise state.
+async fn supervise(
+    addr: SocketAddr,
+    stream: TcpStream,
+    tx: async_channel::Sender<Event>,
+    lines: broadcast::Sender<String>,
+    writer: CommandWriter,
+    resync: bool,
+) {
+    let mut stream = Some(stream);
+    loop {
+        let stream = match stream.take() {
+            Some(stream) => stream,
+            None => reconnect(&addr).await,
+        };
+
+        // Drop writer here as the CommandWriter creates a new connection each time it writes
+        let (rd, _) = tokio::io::split(stream);
+        let line_reader = LineReader::with_line_sender(rd, lines.clone());
+
+        if tx.send(Event::Connected).await.is_err() {
+            return;
+        }
+        if resync {
+            if let Err(e) = writer.queue(RESYNC_COMMAND) {
+                log::error!("Error resynchronising state {:?}", e);
+            }
+        }
+
+        if let Err(e) = stream_reader(line_reader, tx.clone()).await {
+            log::warn!("Console connection lost {:?}", e);
+        }
+
+        if tx.send(Event::Disconnected).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Connect to `addr`, doubling the wait between attempts up to [`RECONNECT_MAX`].
+async fn reconnect(addr: &SocketAddr) -> TcpStream {
+    let mut backoff = RECONNECT_MIN;
+    loop {
+        sleep(backoff).await;
+        backoff = Duration::min(backoff * 2, RECONNECT_MAX);
+        match TcpStream::connect(addr).await {
+            Ok(stream) => return stream,
+            Err(e) => log::info!("Reconnect failed, retrying in {:?} {:?}", backoff, e),
+        }
+    }
+}
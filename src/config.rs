@@ -0,0 +1,142 @@
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::animation::AnimationConfig;
+use crate::chat_commands::ChatCommandConfig;
+use crate::clan_tag::ClanTagConfig;
+use crate::constants::{PORT, STATUS_INTERVAL, TICK_COMMAND, TICK_TIME};
+use crate::types::GenericResult;
+
+/// Settings loaded from a TOML config file, every field is optional and defaults to the
+/// values in [`crate::constants`].
+///
+/// ```toml
+/// host = "192.168.1.20"
+/// port = 5555
+/// tick_interval = 500
+/// tick_command = "clan;incrementvar cl_hud_color 0 5 1"
+/// status_interval = 10000
+/// listeners = ["discord"]
+/// log_level = "info"
+/// json = false
+/// websocket_address = "127.0.0.1:5556"
+/// websocket_origins = ["http://localhost:3000"]
+/// http_address = "127.0.0.1:5557"
+/// database = "netcontool.db"
+/// rules_file = "rules.toml"
+/// scripts = ["kills.rhai"]
+///
+/// [chat_commands]
+/// prefix = "!"
+/// cooldown = 5000
+/// allowlist = ["STEAM_1:0:1234"]
+///
+/// [clan_tag]
+/// frames = ["R{round}", "{adr} ADR"]
+///
+/// [[animations]]
+/// cvar = "cl_crosshairsize"
+/// keyframes = [{ time = 0, value = 2 }, { time = 1000, value = 5 }, { time = 2000, value = 2 }]
+/// repeat = true
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Host the game is running on
+    pub host: String,
+    /// Port passed to the game with `-netconport`
+    pub port: u16,
+    /// Time between ticks in milliseconds
+    pub tick_interval: u64,
+    /// Command sent every tick while enabled and in game
+    pub tick_command: String,
+    /// Time between `status` queries while in game in milliseconds, 0 to disable
+    pub status_interval: u64,
+    /// Names of the state listeners to register
+    pub listeners: Vec<String>,
+    /// Log filter in `env_logger` syntax, e.g. `info` or `csgo_netcon=debug`
+    pub log_level: String,
+    /// Print events and state changes to stdout as JSON lines
+    pub json: bool,
+    /// Address the `websocket` listener binds to
+    pub websocket_address: String,
+    /// Browser origins allowed to connect to the `websocket` listener, connections without an
+    /// `Origin` header are always allowed
+    pub websocket_origins: Vec<String>,
+    /// Address the `http` listener binds to
+    pub http_address: String,
+    /// Path of the SQLite database the `sqlite` listener records matches to
+    pub database: PathBuf,
+    /// Path of a TOML file of automation rules, see [`crate::rules`]
+    pub rules_file: Option<PathBuf>,
+    /// Rhai scripts to run, see [`crate::scripting`]
+    pub scripts: Vec<PathBuf>,
+    /// Settings for the `chat_commands` listener
+    pub chat_commands: ChatCommandConfig,
+    /// Clan tag animation sent on ticks
+    pub clan_tag: ClanTagConfig,
+    /// Cvars animated on ticks, see [`crate::animation`]
+    pub animations: Vec<AnimationConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            host: String::from("127.0.0.1"),
+            port: PORT,
+            tick_interval: TICK_TIME.as_millis() as u64,
+            tick_command: String::from(TICK_COMMAND),
+            status_interval: STATUS_INTERVAL.as_millis() as u64,
+            // Discord is only available with the rpc feature
+            listeners: if cfg!(feature = "rpc") {
+                vec![String::from("discord")]
+            } else {
+                Vec::new()
+            },
+            log_level: String::from("info"),
+            json: false,
+            websocket_address: String::from("127.0.0.1:5556"),
+            websocket_origins: Vec::new(),
+            http_address: String::from("127.0.0.1:5557"),
+            database: PathBuf::from("netcontool.db"),
+            rules_file: None,
+            scripts: Vec::new(),
+            chat_commands: ChatCommandConfig::default(),
+            clan_tag: ClanTagConfig::default(),
+            animations: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> GenericResult<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    /// Resolve the host and port of the netcon port.
+    pub fn addr(&self) -> GenericResult<SocketAddr> {
+        (self.host.as_str(), self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| format!("Could not resolve host {:?}", self.host).into())
+    }
+
+    pub fn tick_time(&self) -> Duration {
+        Duration::from_millis(self.tick_interval)
+    }
+
+    pub fn status_interval(&self) -> Option<Duration> {
+        match self.status_interval {
+            0 => None,
+            interval => Some(Duration::from_millis(interval)),
+        }
+    }
+
+    pub fn listener_enabled(&self, name: &str) -> bool {
+        self.listeners.iter().any(|listener| listener == name)
+    }
+}
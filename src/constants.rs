@@ -4,4 +4,15 @@ pub const BUF_SIZE: usize = 5012;
 pub const NEWLINE: u8 = b'\n';
 pub const PORT: u16 = 5555;
 pub const TICK_TIME: Duration = Duration::from_millis(500);
-pub const TICK_COMMAND: &[u8] = b"clan;incrementvar cl_hud_color 0 5 1\n";
+pub const TICK_COMMAND: &str = "clan;incrementvar cl_hud_color 0 5 1";
+pub const RECONNECT_MIN: Duration = Duration::from_secs(1);
+pub const RECONNECT_MAX: Duration = Duration::from_secs(30);
+pub const RESYNC_COMMAND: &str = "status;game_type;game_mode";
+/// Commands that only print state, so writing one twice in a batch is pointless
+pub const STATE_QUERIES: [&str; 3] = ["status", "game_type", "game_mode"];
+pub const STATUS_INTERVAL: Duration = Duration::from_secs(10);
+/// Lines of console output buffered for each subscriber, `cvarlist` alone prints thousands
+pub const LINE_CHANNEL_SIZE: usize = 16384;
+pub const WRITE_SETTLE_TIME: Duration = Duration::from_millis(250);
+pub const MAX_COMMAND_LENGTH: usize = 512;
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
@@ -0,0 +1,92 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::types::Event;
+use crate::writer::CommandError;
+use crate::NetconClient;
+
+/// First characters of every cvar and command name, for listing them a piece at a time
+const CVARLIST_PREFIXES: &str = "abcdefghijklmnopqrstuvwxyz+-_";
+
+/// Names of the cvars and commands the game is known to have, shared between clones.
+#[derive(Debug, Clone, Default)]
+pub struct Cvars {
+    names: Arc<RwLock<BTreeSet<String>>>,
+}
+
+impl Cvars {
+    pub fn insert(&self, name: &str) -> bool {
+        self.names.write().unwrap().insert(name.to_string())
+    }
+
+    /// Remember any cvar the console printed the value of.
+    pub fn learn(&self, event: &Event) {
+        if let Event::ConVar(name, _) = event {
+            self.insert(name);
+        }
+    }
+
+    /// Learn every name in the output of `cvarlist`, returns how many were new.
+    pub fn learn_cvarlist<S: AsRef<str>>(&self, lines: &[S]) -> usize {
+        lines
+            .iter()
+            .filter_map(|line| parse_cvarlist_line(line.as_ref()))
+            .filter(|name| self.insert(name))
+            .count()
+    }
+
+    /// Learn every name from `cvarlist`, returns how many were new.
+    ///
+    /// The full list is thousands of lines, if the client cannot keep up with that much output
+    /// at once it is listed again one first character at a time.
+    pub async fn load_cvarlist(
+        &self,
+        client: &NetconClient,
+        timeout: Duration,
+    ) -> Result<usize, CommandError> {
+        match client.query_timeout("cvarlist", timeout).await {
+            Ok(lines) => Ok(self.learn_cvarlist(&lines)),
+            Err(CommandError::Lagged(skipped)) => {
+                log::debug!("Missed {} lines of cvarlist, listing in pieces", skipped);
+                let mut learned = 0;
+                for prefix in CVARLIST_PREFIXES.chars() {
+                    let command = format!("cvarlist {}", prefix);
+                    learned += self.learn_cvarlist(&client.query_timeout(&command, timeout).await?);
+                }
+                Ok(learned)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Known names starting with `prefix` in alphabetical order.
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        self.names
+            .read()
+            .unwrap()
+            .range(prefix.to_string()..)
+            .take_while(|name| name.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Lines of `cvarlist` look like `name   : value   : flags   : description`.
+pub fn parse_cvarlist_line(line: &str) -> Option<&str> {
+    let (name, _) = line.split_once(" : ")?;
+    let name = name.trim();
+    if name.is_empty() || name.contains(' ') {
+        None
+    } else {
+        Some(name)
+    }
+}
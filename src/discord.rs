@@ -1,26 +1,29 @@
-use std::sync::mpsc::{channel, Receiver, Sender};
-use std::sync::Mutex;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
 use std::thread;
 use std::time::Duration;
 
+use async_trait::async_trait;
 use discord_presence::models::{Activity, ActivityAssets, ActivityButton, ActivityParty};
 use discord_presence::Client;
 
-use crate::types::{HostType, State, StateListener, Status, UIState};
-
-static mut SENDER: Option<Mutex<Sender<State>>> = None;
+use csgo_netcon::listener::Context;
+use csgo_netcon::types::{HostType, State, Status, UIState};
+use csgo_netcon::Listener;
 
 fn client_thread(rx: Receiver<State>) {
-    eprintln!("RPC starting");
+    log::info!("RPC starting");
     let mut client = Client::new(425776052565049354);
     client.start();
 
-    eprintln!("RPC ready");
-    let mut state = rx.recv().unwrap();
+    log::info!("RPC ready");
+    let mut state = match rx.recv() {
+        Ok(state) => state,
+        Err(_) => return,
+    };
     let mut updated = true;
     loop {
         if updated {
-            eprintln!("RPC received {:?}", state);
+            log::debug!("RPC received {:?}", state);
             let state_string = match state.ui_state {
                 UIState::MainMenu => Some(String::from("In the main menu")),
                 UIState::LoadingScreen => Some(String::from("Loading...")),
@@ -30,8 +33,8 @@ fn client_thread(rx: Receiver<State>) {
                 UIState::PauseMenu => Some(String::from("Tabbed out of a game")),
             };
 
-            let damage_string = if state.round > 0 {
-                format!("{} ADR", state.total_damage_given / state.round as u64)
+            let damage_string = if !state.rounds.is_empty() {
+                format!("{:.0} ADR", state.adr())
             } else {
                 format!("{} DMG", state.total_damage_given)
             };
@@ -92,36 +95,47 @@ fn client_thread(rx: Receiver<State>) {
             ..Activity::default()
         }
             }) {
-                eprintln!("RPC error: {:?}", e);
+                log::error!("RPC error: {:?}", e);
             }
         }
         // Sleep to avoid spamming discord
         thread::sleep(Duration::from_secs(1));
 
         updated = false;
-        while let Ok(new_state) = rx.try_recv() {
-            state = new_state;
-            updated = true;
+        loop {
+            match rx.try_recv() {
+                Ok(new_state) => {
+                    state = new_state;
+                    updated = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                // The listener has shut down
+                Err(TryRecvError::Disconnected) => return,
+            }
         }
     }
 }
 
-fn listener(state: State) {
-    unsafe {
-        if let Some(mutex) = &SENDER {
-            let lock = mutex.lock().unwrap();
-            lock.send(state).unwrap();
-        }
+/// Shows the current game as Discord rich presence, the Discord client runs on its own thread.
+pub struct DiscordListener {
+    tx: Sender<State>,
+}
+
+impl DiscordListener {
+    pub fn new() -> Self {
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            client_thread(rx);
+        });
+        Self { tx }
     }
 }
 
-pub fn register_listener(listeners: &mut Vec<StateListener>) {
-    let (tx, rx) = channel();
-    unsafe {
-        SENDER = Some(Mutex::new(tx));
+#[async_trait]
+impl Listener for DiscordListener {
+    async fn on_state_change(&mut self, _ctx: &Context, state: &State) {
+        if self.tx.send(state.clone()).is_err() {
+            log::error!("RPC thread stopped");
+        }
     }
-    listeners.push(listener);
-    thread::spawn(move || {
-        client_thread(rx);
-    });
 }
@@ -0,0 +1,139 @@
+use csgo_netcon::config::Config;
+use csgo_netcon::storage::{MatchFilter, MatchStats, MatchSummary, Storage};
+use csgo_netcon::types::GenericResult;
+
+use crate::cli::Format;
+
+const MATCH_HEADERS: [&str; 9] = [
+    "id", "started", "minutes", "map", "mode", "rounds", "given", "taken", "adr",
+];
+
+const STATS_HEADERS: [&str; 7] = [
+    "matches",
+    "rounds",
+    "given",
+    "taken",
+    "kills",
+    "adr",
+    "taken/round",
+];
+
+pub fn history(config: &Config, filter: &MatchFilter, format: Format) -> GenericResult<()> {
+    let matches = Storage::open(&config.database)?.matches(filter)?;
+    if format == Format::Json {
+        println!("{}", serde_json::to_string_pretty(&matches)?);
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = matches.iter().map(match_row).collect();
+    print_rows(&MATCH_HEADERS, &rows, format);
+    Ok(())
+}
+
+pub fn stats(config: &Config, filter: &MatchFilter, format: Format) -> GenericResult<()> {
+    let stats = Storage::open(&config.database)?.stats(filter)?;
+    if format == Format::Json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    print_rows(&STATS_HEADERS, &[stats_row(&stats)], format);
+    Ok(())
+}
+
+fn match_row(summary: &MatchSummary) -> Vec<String> {
+    let minutes = summary
+        .ended_at
+        .map(|ended_at| (ended_at.saturating_sub(summary.started_at) / 60_000).to_string())
+        .unwrap_or_default();
+    vec![
+        summary.id.to_string(),
+        format_time(summary.started_at),
+        minutes,
+        summary.map.clone(),
+        summary.game_mode.clone().unwrap_or_default(),
+        summary.rounds.to_string(),
+        summary.damage_given.to_string(),
+        summary.damage_taken.to_string(),
+        format!("{:.1}", summary.adr),
+    ]
+}
+
+fn stats_row(stats: &MatchStats) -> Vec<String> {
+    vec![
+        stats.matches.to_string(),
+        stats.rounds.to_string(),
+        stats.damage_given.to_string(),
+        stats.damage_taken.to_string(),
+        stats.kills.to_string(),
+        format!("{:.1}", stats.adr),
+        format!("{:.1}", stats.average_damage_taken),
+    ]
+}
+
+fn print_rows(headers: &[&str], rows: &[Vec<String>], format: Format) {
+    match format {
+        Format::Csv => {
+            println!("{}", headers.join(","));
+            for row in rows {
+                let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+                println!("{}", fields.join(","));
+            }
+        }
+        Format::Table | Format::Json => {
+            let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+            for row in rows {
+                for (width, field) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(field.chars().count());
+                }
+            }
+            let print_row = |fields: Vec<&str>| {
+                let padded: Vec<String> = fields
+                    .iter()
+                    .zip(&widths)
+                    .map(|(field, width)| format!("{:width$}", field, width = width))
+                    .collect();
+                println!("{}", padded.join("  ").trim_end());
+            };
+            print_row(headers.to_vec());
+            for row in rows {
+                print_row(row.iter().map(String::as_str).collect());
+            }
+        }
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Format milliseconds since the unix epoch as a UTC date and time.
+fn format_time(millis: u64) -> String {
+    let seconds = millis / 1000;
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+
+    // Days to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
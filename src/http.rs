@@ -0,0 +1,153 @@
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use axum::extract::State as AxumState;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+use csgo_netcon::listener::Context;
+use csgo_netcon::types::{GenericResult, Player, RoundRecord, State, Status, StatusData};
+use csgo_netcon::{CommandWriter, Listener};
+
+#[derive(Default)]
+struct Shared {
+    state: State,
+    /// The last status received, kept after disconnecting from a server
+    status: Option<StatusData>,
+}
+
+#[derive(Clone)]
+struct AppState {
+    shared: Arc<RwLock<Shared>>,
+    writer: Option<CommandWriter>,
+}
+
+/// The body of `POST /command`, e.g. `{"command": "say gg"}`
+#[derive(Deserialize)]
+struct CommandBody {
+    command: String,
+}
+
+/// A small REST API for scripts that do not want to speak netcon.
+///
+/// * `GET /state` the current state
+/// * `GET /status` the last `status` output
+/// * `GET /players` the player list from the last `status` output
+/// * `GET /rounds` every round this game with damage per opponent
+/// * `POST /command` send `{"command": "..."}` to the console
+pub struct HttpServer {
+    shared: Arc<RwLock<Shared>>,
+}
+
+impl HttpServer {
+    pub async fn start(address: &str, writer: Option<CommandWriter>) -> GenericResult<Self> {
+        let listener = TcpListener::bind(address).await?;
+        log::info!("HTTP server listening on {}", listener.local_addr()?);
+
+        let shared = Arc::new(RwLock::new(Shared::default()));
+        let app = Router::new()
+            .route("/state", get(get_state))
+            .route("/status", get(get_status))
+            .route("/players", get(get_players))
+            .route("/rounds", get(get_rounds))
+            .route("/command", post(post_command))
+            .with_state(AppState {
+                shared: shared.clone(),
+                writer,
+            });
+
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                log::error!("HTTP server error {:?}", e);
+            }
+        });
+
+        Ok(Self { shared })
+    }
+
+    pub fn send_state(&self, state: &State) {
+        let mut shared = self.shared.write().unwrap();
+        shared.state = state.clone();
+        if let Status::Connected(data) = &state.status {
+            shared.status = Some(data.clone());
+        }
+    }
+}
+
+#[async_trait]
+impl Listener for HttpServer {
+    async fn on_state_change(&mut self, _ctx: &Context, state: &State) {
+        self.send_state(state);
+    }
+}
+
+async fn get_state(AxumState(app): AxumState<AppState>) -> Json<State> {
+    Json(app.shared.read().unwrap().state.clone())
+}
+
+async fn get_status(
+    AxumState(app): AxumState<AppState>,
+) -> Result<Json<StatusData>, (StatusCode, Json<Value>)> {
+    app.shared
+        .read()
+        .unwrap()
+        .status
+        .clone()
+        .map(Json)
+        .ok_or_else(not_connected)
+}
+
+async fn get_players(
+    AxumState(app): AxumState<AppState>,
+) -> Result<Json<Vec<Player>>, (StatusCode, Json<Value>)> {
+    app.shared
+        .read()
+        .unwrap()
+        .status
+        .as_ref()
+        .map(|status| Json(status.player_list.clone()))
+        .ok_or_else(not_connected)
+}
+
+async fn get_rounds(AxumState(app): AxumState<AppState>) -> Json<Vec<RoundRecord>> {
+    Json(app.shared.read().unwrap().state.rounds.clone())
+}
+
+async fn post_command(
+    AxumState(app): AxumState<AppState>,
+    Json(body): Json<CommandBody>,
+) -> (StatusCode, Json<Value>) {
+    let writer = match &app.writer {
+        Some(writer) => writer,
+        None => {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(
+                    json!({ "command": body.command, "error": "Commands cannot be sent while replaying" }),
+                ),
+            )
+        }
+    };
+
+    match writer.send(body.command.as_str()).await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(json!({ "command": body.command, "result": "ok" })),
+        ),
+        Err(e) => (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({ "command": body.command, "error": e.to_string() })),
+        ),
+    }
+}
+
+fn not_connected() -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({ "error": "No status has been received yet" })),
+    )
+}
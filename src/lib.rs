@@ -0,0 +1,23 @@
+pub mod animation;
+pub mod chat_commands;
+pub mod clan_tag;
+pub mod client;
+pub mod config;
+pub mod constants;
+pub mod cvars;
+pub mod listener;
+pub mod reader;
+pub mod recording;
+pub mod rules;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+#[cfg(feature = "sqlite")]
+pub mod storage;
+pub mod stream_reader;
+pub mod types;
+pub mod writer;
+
+pub use crate::client::{ClientOptions, NetconClient};
+pub use crate::listener::Listener;
+pub use crate::reader::LineReader;
+pub use crate::writer::{CommandError, CommandWriter};
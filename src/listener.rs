@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use tokio::task::JoinHandle;
+
+use crate::types::{Event, State};
+use crate::writer::{CommandError, CommandWriter};
+
+/// Passed to every listener hook, gives listeners a way to send commands back to the console.
+#[derive(Clone)]
+pub struct Context {
+    writer: Option<CommandWriter>,
+}
+
+impl Context {
+    /// Commands are only sent if there is a `writer`, e.g. they are dropped while replaying.
+    pub fn new(writer: Option<CommandWriter>) -> Self {
+        Self { writer }
+    }
+
+    /// Send a command and wait until it has been written to the console.
+    pub async fn send(&self, command: impl Into<String>) -> Result<(), CommandError> {
+        match &self.writer {
+            Some(writer) => writer.send(command).await,
+            None => Err(CommandError::Closed),
+        }
+    }
+
+    /// Queue a command without waiting for it to be written.
+    pub fn queue(&self, command: impl Into<String>) -> Result<(), CommandError> {
+        match &self.writer {
+            Some(writer) => writer.queue(command),
+            None => Err(CommandError::Closed),
+        }
+    }
+}
+
+/// An integration that reacts to events and state changes.
+///
+/// Each listener runs in its own task and receives notifications in order, so a slow listener
+/// does not hold up the others. All hooks default to doing nothing.
+#[async_trait]
+pub trait Listener: Send {
+    /// Called when the console connection is established or re-established.
+    async fn on_connect(&mut self, _ctx: &Context) {}
+
+    /// Called for every event, including [`Event::Tick`].
+    async fn on_event(&mut self, _ctx: &Context, _event: &Event) {}
+
+    /// Called with a snapshot of the state whenever it changes.
+    async fn on_state_change(&mut self, _ctx: &Context, _state: &State) {}
+
+    /// Called once before the program exits.
+    async fn on_shutdown(&mut self, _ctx: &Context) {}
+}
+
+enum Notification {
+    Connect,
+    Event(Event),
+    StateChange(State),
+    Shutdown,
+}
+
+/// The set of registered listeners.
+pub struct Listeners {
+    ctx: Context,
+    senders: Vec<async_channel::Sender<Notification>>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Listeners {
+    pub fn new(ctx: Context) -> Self {
+        Self {
+            ctx,
+            senders: Vec::new(),
+            tasks: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, listener: Box<dyn Listener>) {
+        let (tx, rx) = async_channel::unbounded();
+        let ctx = self.ctx.clone();
+        self.senders.push(tx);
+        self.tasks
+            .push(tokio::spawn(run_listener(listener, ctx, rx)));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.senders.is_empty()
+    }
+
+    pub fn event(&self, event: &Event) {
+        if *event == Event::Connected {
+            self.notify(|| Notification::Connect);
+        }
+        self.notify(|| Notification::Event(event.clone()));
+    }
+
+    pub fn state_change(&self, state: &State) {
+        self.notify(|| Notification::StateChange(state.clone()));
+    }
+
+    /// Call [`Listener::on_shutdown`] on every listener and wait for them to finish.
+    pub async fn shutdown(self) {
+        self.notify(|| Notification::Shutdown);
+        for task in self.tasks {
+            if let Err(e) = task.await {
+                log::error!("Listener panicked {:?}", e);
+            }
+        }
+    }
+
+    fn notify<F: Fn() -> Notification>(&self, notification: F) {
+        for sender in &self.senders {
+            // A listener that panicked has dropped its receiver
+            let _ = sender.try_send(notification());
+        }
+    }
+}
+
+async fn run_listener(
+    mut listener: Box<dyn Listener>,
+    ctx: Context,
+    rx: async_channel::Receiver<Notification>,
+) {
+    while let Ok(notification) = rx.recv().await {
+        match notification {
+            Notification::Connect => listener.on_connect(&ctx).await,
+            Notification::Event(event) => listener.on_event(&ctx, &event).await,
+            Notification::StateChange(state) => listener.on_state_change(&ctx, &state).await,
+            Notification::Shutdown => {
+                listener.on_shutdown(&ctx).await;
+                return;
+            }
+        }
+    }
+}
@@ -1,179 +1,286 @@
-use std::net::SocketAddr;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use tokio::io::AsyncWriteExt;
-use tokio::net::TcpStream;
+use clap::Parser;
+use tokio::sync::broadcast;
 use tokio::time::sleep;
 
-pub mod constants;
+use csgo_netcon::animation::CvarAnimator;
+use csgo_netcon::chat_commands::ChatCommandRouter;
+use csgo_netcon::clan_tag::ClanTagAnimator;
+use csgo_netcon::config::Config;
+use csgo_netcon::constants::LINE_CHANNEL_SIZE;
+use csgo_netcon::listener::{Context, Listeners};
+use csgo_netcon::recording;
+use csgo_netcon::rules::{Action, Rules, StateAction};
+#[cfg(feature = "scripting")]
+use csgo_netcon::scripting::ScriptListener;
+#[cfg(feature = "sqlite")]
+use csgo_netcon::storage;
+use csgo_netcon::types::{
+    Event, EventDiscriminants, GenericResult, State, StatusDiscriminants, UIState,
+};
+use csgo_netcon::{ClientOptions, CommandWriter, Listener, NetconClient};
+
+mod cli;
 #[cfg(feature = "rpc")]
 mod discord;
-pub mod reader;
-pub mod stream_reader;
-pub mod types;
-use crate::constants::{PORT, TICK_COMMAND, TICK_TIME};
-use crate::reader::LineReader;
-use crate::stream_reader::stream_reader;
-use crate::types::{
-    DamageDirection, Event, EventDiscriminants, GameMode, GameType, GenericResult, State,
-    StateListener, Status, StatusDiscriminants, UIState,
-};
+#[cfg(feature = "sqlite")]
+mod history;
+#[cfg(feature = "http")]
+mod http;
+mod output;
+mod send;
+#[cfg(feature = "repl")]
+mod shell;
+mod webhook;
+#[cfg(feature = "websocket")]
+mod websocket;
 
 #[tokio::main]
 async fn main() -> GenericResult<()> {
-    let (tx, rx) = async_channel::unbounded();
+    let args = cli::Args::parse();
+    let config = args.config()?;
+    env_logger::Builder::new()
+        .parse_filters(&config.log_level)
+        .init();
 
-    // Make connection
-    eprintln!("Making TCP Connection");
-    let stream: TcpStream;
-    let addr = SocketAddr::from(([127, 0, 0, 1], PORT));
-    loop {
-        match TcpStream::connect(&addr).await {
-            Ok(s) => {
-                stream = s;
-                break;
-            }
-            _ => {}
+    match &args.command {
+        Some(cli::Command::Replay { file, fast }) => {
+            log::info!("Replaying {:?}", file);
+            let events = recording::replay(file, !fast).await?;
+            return run(&config, events, None).await;
+        }
+        Some(cli::Command::Send {
+            command,
+            wait,
+            timeout,
+        }) => {
+            let code = send::send(
+                &config,
+                command,
+                wait.as_ref(),
+                Duration::from_secs(*timeout),
+            )
+            .await;
+            std::process::exit(code);
         }
-        sleep(Duration::from_secs(1)).await;
+        #[cfg(feature = "repl")]
+        Some(cli::Command::Shell { history }) => return shell::shell(&config, history).await,
+        #[cfg(feature = "sqlite")]
+        Some(cli::Command::History {
+            filter,
+            limit,
+            format,
+        }) => return history::history(&config, &filter.to_filter(Some(*limit)), *format),
+        #[cfg(feature = "sqlite")]
+        Some(cli::Command::Stats { filter, format }) => {
+            return history::stats(&config, &filter.to_filter(None), *format)
+        }
+        None => {}
     }
-    // Drop writer here as it is necessary to create a new connection each time we want to write.
-    // This is due to CS:GO crashing if 2 or more writes are made to a socket in between each read,
-    // but reads cannot be made without console output
-    let (rd, _) = tokio::io::split(stream);
-    let line_reader = LineReader::new(rd);
-
-    let mut listeners: Vec<StateListener> = Vec::new();
-    #[cfg(feature = "rpc")]
-    discord::register_listener(&mut listeners);
 
-    eprintln!("Connected...");
+    // Make connection
+    log::info!("Making TCP Connection");
+    let addr = config.addr()?;
+    let mut options = ClientOptions::default();
+    if let Some(file) = &args.record {
+        // Subscribe before connecting so the recording starts with the first line
+        let (lines, _) = broadcast::channel(LINE_CHANNEL_SIZE);
+        log::info!("Recording to {:?}", file);
+        recording::record(lines.subscribe(), file).await?;
+        options.lines = Some(lines);
+    }
+    let client = NetconClient::connect_retry_with(addr, Duration::from_secs(1), options).await;
+    log::info!("Connected...");
 
     {
-        let tx = tx.clone();
+        let tx = client.event_sender();
+        let tick_time = config.tick_time();
         tokio::spawn(async move {
-            stream_reader(line_reader, tx).await.unwrap();
-            ()
+            let mut tick_no = 0u8;
+            loop {
+                tx.send(Event::Tick(tick_no)).await.unwrap();
+                tick_no = u8::wrapping_add(tick_no, 1);
+                sleep(tick_time).await;
+            }
         });
     }
 
-    let mut state = State::default();
-    call_state_update_listeners(&listeners, &state);
-
-    tokio::spawn(async move {
-        let mut tick_no = 0u8;
-        loop {
-            tx.send(Event::Tick(tick_no)).await.unwrap();
-            tick_no = u8::wrapping_add(tick_no, 1);
-            sleep(TICK_TIME).await;
+    run(&config, client.events(), Some(client.writer())).await
+}
+
+/// Update state from events until the event stream ends or the program is interrupted, commands
+/// are only sent if there is a `writer`.
+async fn run(
+    config: &Config,
+    events: async_channel::Receiver<Event>,
+    writer: Option<CommandWriter>,
+) -> GenericResult<()> {
+    let mut listeners = Listeners::new(Context::new(writer.clone()));
+    for name in &config.listeners {
+        if let Some(listener) = create_listener(name, config, &writer).await? {
+            log::info!("Registered listener {:?}", name);
+            listeners.register(listener);
+        }
+    }
+    if config.json && !config.listener_enabled("json") {
+        listeners.register(Box::new(output::JsonOutput));
+    }
+    for path in &config.scripts {
+        #[cfg(feature = "scripting")]
+        {
+            listeners.register(Box::new(ScriptListener::load(path)?));
+            log::info!("Loaded script {:?}", path);
         }
-    });
+        #[cfg(not(feature = "scripting"))]
+        log::warn!(
+            "Skipping script {:?}, it needs the scripting cargo feature",
+            path
+        );
+    }
+
+    let mut rules = match &config.rules_file {
+        Some(path) => Rules::load(path)?,
+        None => Rules::default(),
+    };
+    if !rules.is_empty() {
+        log::info!("Loaded {} rules", rules.len());
+    }
+
+    let mut clan_tag = ClanTagAnimator::new(&config.clan_tag);
+    let mut animator = CvarAnimator::new(&config.animations);
+
+    let mut last_status_poll = Instant::now();
+
+    let mut state = State::default();
+    listeners.state_change(&state);
 
     loop {
-        let event = rx.recv().await;
-        if let Ok(event) = event {
-            if !event.is_variant(EventDiscriminants::Tick) {
-                eprintln!("{:?}", event);
+        let event = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => event,
+                Err(_) => break,
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        };
+
+        if !event.is_variant(EventDiscriminants::Tick) {
+            log::debug!("{:?}", event);
+        }
+
+        if let (Event::Tick(_), Some(interval)) = (&event, config.status_interval()) {
+            // Keep the player list fresh so joins and leaves are noticed
+            if state.ui_state == UIState::InGame && last_status_poll.elapsed() >= interval {
+                last_status_poll = Instant::now();
+                queue_command(&writer, "status");
             }
+        }
 
-            match event {
-                Event::Command(command) => match command.as_str() {
-                    "toggle" => state.enabled = !state.enabled,
-                    "start" => {
-                        state.clear_game_data(state.map.clone());
-                        call_state_update_listeners(&listeners, &state);
-                    }
-                    "addround" => {
-                        state.round += 1;
-                        call_state_update_listeners(&listeners, &state);
-                    }
-                    _ => {
-                        eprintln!("Sending command {:?}", command);
-                        send_command(&addr, &format!("{}\n", command).into_bytes()).await?;
-                    }
-                },
-                Event::ChangeUIState(_, new_state) => {
-                    state.ui_state = new_state;
-                    if state.ui_state == UIState::MainMenu {
-                        state.clear_game_data(None);
-                    }
-                    if state.ui_state == UIState::InGame
-                        && state.status.is_variant(StatusDiscriminants::NotConnected)
-                    {
-                        send_command(&addr, b"status\n").await?;
-                    }
-                    call_state_update_listeners(&listeners, &state);
-                }
-                Event::Status(new_status) => {
-                    state.status = new_status;
-                    if let Status::Connected(data) = &state.status {
-                        state.map = Some(data.map.clone());
-                    } else {
-                        state.clear_game_data(None);
-                    }
-                    call_state_update_listeners(&listeners, &state);
-                }
-                Event::MapChange(map) => {
-                    state.clear_game_data(Some(map));
-                    call_state_update_listeners(&listeners, &state);
-                }
-                Event::EnterBuyPeriod => {
-                    state.round += 1;
-                    call_state_update_listeners(&listeners, &state);
-                }
-                Event::Damage(damage) => {
-                    if damage.direction == DamageDirection::Given {
-                        state.total_damage_given += u8::max(damage.amount, 100) as u64;
-                    } else {
-                        state.total_damage_taken += u8::max(damage.amount, 100) as u64;
-                    }
-                    call_state_update_listeners(&listeners, &state);
-                }
-                Event::ConVar(name, value) => {
-                    if name == "game_type" {
-                        if let Ok(value) = u8::from_str_radix(&value, 10) {
-                            if let Some(game_type) = GameType::try_from(value) {
-                                state.game_type = game_type;
-                                call_state_update_listeners(&listeners, &state);
-                                continue;
-                            }
-                        }
-                    }
-                    if name == "game_mode" {
-                        if let Ok(value) = u8::from_str_radix(&value, 10) {
-                            if let Some(game_mode) =
-                                GameMode::try_from((state.game_type.clone(), value))
-                            {
-                                state.game_mode = game_mode;
-                                call_state_update_listeners(&listeners, &state);
-                                continue;
+        let mut changed = false;
+        match &event {
+            Event::Command(command) => match StateAction::from_command(command) {
+                Some(action) => changed |= action.apply(&mut state),
+                None => {
+                    if let Some(writer) = writer.clone() {
+                        log::debug!("Sending command {:?}", command);
+                        let command = command.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = writer.send(command.as_str()).await {
+                                log::error!("Error sending command {:?} {}", command, e);
                             }
-                        }
+                        });
                     }
                 }
-                Event::Tick(_)
-                    if state.enabled
-                        && state.ui_state == UIState::InGame
-                        && state.status.is_variant(StatusDiscriminants::Connected) =>
-                {
-                    eprintln!("InGame tick");
-                    send_command(&addr, TICK_COMMAND).await?;
+            },
+            Event::ChangeUIState(_, UIState::InGame)
+                if state.status.is_variant(StatusDiscriminants::NotConnected) =>
+            {
+                queue_command(&writer, "status");
+            }
+            Event::Tick(_)
+                if state.enabled
+                    && state.ui_state == UIState::InGame
+                    && state.status.is_variant(StatusDiscriminants::Connected) =>
+            {
+                log::trace!("InGame tick");
+                queue_command(&writer, &config.tick_command);
+                if let Some(command) = clan_tag.tick(&state, Instant::now()) {
+                    queue_command(&writer, &command);
+                }
+                for command in animator.tick(Instant::now()) {
+                    queue_command(&writer, &command);
                 }
-                _ => {}
+            }
+            _ => {}
+        }
+
+        changed |= state.update(&event);
+        animator.event(&event, &state, Instant::now());
+        for action in rules.evaluate(&event, &state) {
+            match action {
+                Action::Command(command) => queue_command(&writer, &command),
+                Action::State(action) => changed |= action.apply(&mut state),
+                Action::Webhook { url, body } => webhook::post(url, body.unwrap_or_default()),
             }
         }
+        listeners.event(&event);
+        if changed {
+            listeners.state_change(&state);
+        }
     }
-}
 
-async fn send_command(addr: &SocketAddr, data: &[u8]) -> tokio::io::Result<()> {
-    let mut cmd_conn = TcpStream::connect(addr).await?;
-    cmd_conn.write_all(data).await?;
+    log::info!("Shutting down");
+    listeners.shutdown().await;
+
     Ok(())
 }
 
-fn call_state_update_listeners(listeners: &Vec<StateListener>, state: &State) -> () {
-    for listener in listeners {
-        let state = state.clone();
-        listener(state);
+/// Create the listener called `name` in the config.
+// `config` and `writer` are unused when listeners are disabled by features
+#[allow(unused_variables)]
+async fn create_listener(
+    name: &str,
+    config: &Config,
+    writer: &Option<CommandWriter>,
+) -> GenericResult<Option<Box<dyn Listener>>> {
+    Ok(match name {
+        "json" => Some(Box::new(output::JsonOutput)),
+        "chat_commands" => Some(Box::new(ChatCommandRouter::from_config(
+            &config.chat_commands,
+        ))),
+        #[cfg(feature = "rpc")]
+        "discord" => Some(Box::new(discord::DiscordListener::new())),
+        #[cfg(feature = "websocket")]
+        "websocket" => Some(Box::new(
+            websocket::WebSocketServer::start(
+                &config.websocket_address,
+                writer.clone(),
+                config.websocket_origins.clone(),
+            )
+            .await?,
+        )),
+        #[cfg(feature = "http")]
+        "http" => Some(Box::new(
+            http::HttpServer::start(&config.http_address, writer.clone()).await?,
+        )),
+        #[cfg(feature = "sqlite")]
+        "sqlite" => Some(Box::new(storage::StorageListener::new(
+            storage::Storage::open(&config.database)?,
+        ))),
+        _ => {
+            log::warn!(
+                "Unknown listener {:?}, it may need to be enabled with a cargo feature",
+                name
+            );
+            None
+        }
+    })
+}
+
+fn queue_command(writer: &Option<CommandWriter>, command: &str) {
+    if let Some(writer) = writer {
+        if let Err(e) = writer.queue(command) {
+            log::error!("Error queueing command {:?} {}", command, e);
+        }
     }
 }
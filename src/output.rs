@@ -0,0 +1,68 @@
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use csgo_netcon::listener::Context;
+use csgo_netcon::types::{Event, EventDiscriminants, State};
+use csgo_netcon::Listener;
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Output<'a> {
+    Event(&'a Event),
+    State(&'a State),
+}
+
+/// A single line of JSON output.
+#[derive(Serialize)]
+struct Line<'a> {
+    /// Milliseconds since the unix epoch
+    timestamp: u128,
+    #[serde(flatten)]
+    output: Output<'a>,
+}
+
+pub fn event_json(event: &Event) -> String {
+    to_json(Output::Event(event))
+}
+
+pub fn state_json(state: &State) -> String {
+    to_json(Output::State(state))
+}
+
+/// Prints every event except ticks, and every state change, to stdout.
+pub struct JsonOutput;
+
+#[async_trait]
+impl Listener for JsonOutput {
+    async fn on_event(&mut self, _ctx: &Context, event: &Event) {
+        if !event.is_variant(EventDiscriminants::Tick) {
+            print(&event_json(event));
+        }
+    }
+
+    async fn on_state_change(&mut self, _ctx: &Context, state: &State) {
+        print(&state_json(state));
+    }
+}
+
+fn to_json(output: Output) -> String {
+    let line = Line {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default(),
+        output,
+    };
+    // Serializing these types cannot fail, they contain no maps with non-string keys
+    serde_json::to_string(&line).unwrap()
+}
+
+fn print(line: &str) {
+    let mut stdout = std::io::stdout().lock();
+    if let Err(e) = writeln!(stdout, "{}", line) {
+        log::error!("Error writing JSON output {:?}", e);
+    }
+}
@@ -3,6 +3,7 @@ use std::pin::Pin;
 
 use bytes::BytesMut;
 use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::broadcast;
 
 use crate::constants::{BUF_SIZE, NEWLINE};
 
@@ -26,6 +27,7 @@ impl Default for Buffer {
 pub struct LineReader<T: AsyncRead + Send> {
     inner: Pin<Box<T>>,
     buffers: VecDeque<Buffer>,
+    lines: Option<broadcast::Sender<String>>,
 }
 
 impl<T: AsyncRead + Send> LineReader<T> {
@@ -33,19 +35,30 @@ impl<T: AsyncRead + Send> LineReader<T> {
         Self {
             inner: Box::pin(inner),
             buffers: VecDeque::default(),
+            lines: None,
+        }
+    }
+
+    /// Create a reader that also publishes every line it reads to `lines`.
+    pub fn with_line_sender(inner: T, lines: broadcast::Sender<String>) -> Self {
+        Self {
+            lines: Some(lines),
+            ..Self::new(inner)
         }
     }
 
     async fn read_buffer(&mut self) -> tokio::io::Result<()> {
-        loop {
-            let mut buffer = Buffer::default();
-            let end = self.inner.read_buf(&mut buffer.data).await?;
-            if end > 0 {
-                buffer.end = end;
-                self.buffers.push_back(buffer);
-                break;
-            }
+        let mut buffer = Buffer::default();
+        let end = self.inner.read_buf(&mut buffer.data).await?;
+        // A read of 0 bytes into an empty buffer means the socket was closed
+        if end == 0 {
+            return Err(tokio::io::Error::new(
+                tokio::io::ErrorKind::UnexpectedEof,
+                "Console connection closed",
+            ));
         }
+        buffer.end = end;
+        self.buffers.push_back(buffer);
         Ok(())
     }
 
@@ -70,6 +83,10 @@ impl<T: AsyncRead + Send> LineReader<T> {
                 let res = String::from_utf8_lossy(&buffer.data[buffer.start..end]);
                 buffer.start = end + 1;
                 builder.push_str(&res);
+                if let Some(lines) = &self.lines {
+                    // No receivers is not an error, nobody is waiting for output
+                    let _ = lines.send(builder.clone());
+                }
                 return Ok(builder);
             }
             // Read next buffer
@@ -0,0 +1,95 @@
+use std::path::Path;
+use std::time::Duration;
+
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep_until, Instant};
+
+use crate::constants::BUF_SIZE;
+use crate::reader::LineReader;
+use crate::stream_reader::stream_reader;
+use crate::types::{Event, GenericResult};
+
+/// Write every line received on `lines` to a file at `path`.
+///
+/// Each line of the file is the number of milliseconds since recording started, a tab, and
+/// the raw console line.
+pub async fn record(
+    mut lines: broadcast::Receiver<String>,
+    path: &Path,
+) -> GenericResult<JoinHandle<()>> {
+    let mut file = BufWriter::new(File::create(path).await?);
+    let start = Instant::now();
+
+    Ok(tokio::spawn(async move {
+        loop {
+            let line = match lines.recv().await {
+                Ok(line) => line,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Recording skipped {} lines", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let entry = format!("{}\t{}\n", start.elapsed().as_millis(), line);
+            if let Err(e) = file.write_all(entry.as_bytes()).await {
+                log::error!("Error writing recording {:?}", e);
+                break;
+            }
+            // Flush every line so the recording survives a crash
+            if let Err(e) = file.flush().await {
+                log::error!("Error writing recording {:?}", e);
+                break;
+            }
+        }
+    }))
+}
+
+/// Parse a file written by [`record`] into events, as if it was being read from the console.
+///
+/// With `realtime` the original timing between lines is kept, otherwise lines are replayed as
+/// fast as they can be parsed. The channel closes once the whole file has been replayed.
+pub async fn replay(path: &Path, realtime: bool) -> GenericResult<async_channel::Receiver<Event>> {
+    let mut file = BufReader::new(File::open(path).await?).lines();
+    let (mut wr, rd) = tokio::io::duplex(BUF_SIZE);
+    let (tx, rx) = async_channel::unbounded();
+
+    tokio::spawn(async move {
+        let start = Instant::now();
+        loop {
+            let entry = match file.next_line().await {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(e) => {
+                    log::error!("Error reading recording {:?}", e);
+                    break;
+                }
+            };
+            let (millis, line) = match entry.split_once('\t') {
+                Some((millis, line)) => (millis.parse::<u64>().ok(), line),
+                None => (None, entry.as_str()),
+            };
+            if let (true, Some(millis)) = (realtime, millis) {
+                sleep_until(start + Duration::from_millis(millis)).await;
+            }
+            if wr
+                .write_all(format!("{}\n", line).as_bytes())
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+        // Dropping the writer ends the stream reader
+    });
+
+    tokio::spawn(async move {
+        if let Err(e) = stream_reader(LineReader::new(rd), tx).await {
+            log::debug!("Replay finished {:?}", e);
+        }
+    });
+
+    Ok(rx)
+}
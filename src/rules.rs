@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::types::{
+    ChatScope, DamageDirection, Event, GenericResult, State, StatusDiscriminants, UIState,
+};
+use crate::writer::sanitize_argument;
+
+/// A rules file, e.g.
+///
+/// ```toml
+/// [[rule]]
+/// name = "gg"
+/// debounce = 10000
+/// trigger = { event = "chat", text = "(?i)^gg$" }
+/// conditions = { ui_state = "InGame" }
+/// actions = [{ command = "say gg {sender}" }]
+///
+/// [[rule]]
+/// name = "big hit"
+/// trigger = { event = "damage", direction = "Given", above = 90 }
+/// actions = [{ webhook = { url = "https://example.com/hook" } }]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    pub name: String,
+    pub trigger: Trigger,
+    #[serde(default)]
+    pub conditions: Conditions,
+    pub actions: Vec<Action>,
+    /// Minimum time between the rule firing in milliseconds
+    #[serde(default)]
+    pub debounce: u64,
+}
+
+/// The event a rule fires on, every field is an optional filter.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Trigger {
+    Connected,
+    Disconnected,
+    MapChange {
+        map: Option<String>,
+    },
+    UiState {
+        from: Option<UIState>,
+        to: Option<UIState>,
+    },
+    RoundStart,
+    Damage {
+        direction: Option<DamageDirection>,
+        target: Option<String>,
+        /// Only damage strictly above this amount
+        above: Option<u8>,
+    },
+    Kill {
+        attacker: Option<String>,
+        victim: Option<String>,
+        headshot: Option<bool>,
+    },
+    /// `text` and `sender` are regexes, named groups in `text` become template variables
+    Chat {
+        text: Option<String>,
+        sender: Option<String>,
+        scope: Option<ChatScope>,
+    },
+    ConVar {
+        name: String,
+        value: Option<String>,
+    },
+}
+
+/// Checked against the state after the event has been applied.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Conditions {
+    pub map: Option<String>,
+    pub ui_state: Option<UIState>,
+    /// Name of the game mode, e.g. `competitive`
+    pub game_mode: Option<String>,
+    pub enabled: Option<bool>,
+    pub connected: Option<bool>,
+    pub min_round: Option<u8>,
+    pub max_round: Option<u8>,
+}
+
+/// What a rule does when it fires. Commands and webhook bodies are templates where `{name}` is
+/// replaced with a variable from the event, e.g. `{map}`, `{sender}`, `{text}`, `{amount}`, or
+/// `{round}` from the state.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Action {
+    Command(String),
+    State(StateAction),
+    /// POST `body` to `url`, the body defaults to the rule name and event as JSON. Variables are
+    /// JSON escaped so they belong inside quotes, e.g. `{"text": "{text}"}`
+    Webhook {
+        url: String,
+        body: Option<String>,
+    },
+}
+
+/// Changes to the state, the same as the `toggle`, `start` and `addround` console commands.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateAction {
+    Toggle,
+    Enable,
+    Disable,
+    /// Clear the game data and start counting from round zero
+    Start,
+    AddRound,
+}
+
+impl StateAction {
+    pub fn from_command(command: &str) -> Option<Self> {
+        match command {
+            "toggle" => Some(Self::Toggle),
+            "start" => Some(Self::Start),
+            "addround" => Some(Self::AddRound),
+            _ => None,
+        }
+    }
+
+    /// Apply the action to `state`, returns true if the state changed.
+    pub fn apply(&self, state: &mut State) -> bool {
+        match self {
+            Self::Toggle => state.enabled = !state.enabled,
+            Self::Enable | Self::Disable => {
+                let enabled = *self == Self::Enable;
+                if state.enabled == enabled {
+                    return false;
+                }
+                state.enabled = enabled;
+            }
+            Self::Start => state.clear_game_data(state.map.clone()),
+            Self::AddRound => state.start_round(),
+        }
+        true
+    }
+}
+
+struct Rule {
+    config: RuleConfig,
+    text: Option<Regex>,
+    sender: Option<Regex>,
+    last_fired: Option<Instant>,
+}
+
+impl Rule {
+    fn new(config: RuleConfig) -> GenericResult<Self> {
+        let (text, sender) = match &config.trigger {
+            Trigger::Chat { text, sender, .. } => (
+                text.as_deref().map(Regex::new).transpose()?,
+                sender.as_deref().map(Regex::new).transpose()?,
+            ),
+            _ => (None, None),
+        };
+        Ok(Self {
+            config,
+            text,
+            sender,
+            last_fired: None,
+        })
+    }
+
+    /// The template variables from `event` if it triggers this rule
+    fn trigger(&self, event: &Event) -> Option<HashMap<String, String>> {
+        let mut vars = HashMap::new();
+        match (&self.config.trigger, event) {
+            (Trigger::Connected, Event::Connected) => {}
+            (Trigger::Disconnected, Event::Disconnected) => {}
+            (Trigger::RoundStart, Event::EnterBuyPeriod) => {}
+            (Trigger::MapChange { map }, Event::MapChange(new_map)) => {
+                if !matches_option(map, new_map) {
+                    return None;
+                }
+                vars.insert("map".into(), new_map.clone());
+            }
+            (Trigger::UiState { from, to }, Event::ChangeUIState(old_state, new_state)) => {
+                if !matches_option(from, old_state) || !matches_option(to, new_state) {
+                    return None;
+                }
+                vars.insert("from".into(), format!("{:?}", old_state));
+                vars.insert("to".into(), format!("{:?}", new_state));
+            }
+            (
+                Trigger::Damage {
+                    direction,
+                    target,
+                    above,
+                },
+                Event::Damage(damage),
+            ) => {
+                if !matches_option(direction, &damage.direction)
+                    || !matches_option(target, &damage.target)
+                    || above.is_some_and(|above| damage.amount <= above)
+                {
+                    return None;
+                }
+                vars.insert("target".into(), damage.target.clone());
+                vars.insert("amount".into(), damage.amount.to_string());
+                vars.insert("hits".into(), damage.hits.to_string());
+            }
+            (
+                Trigger::Kill {
+                    attacker,
+                    victim,
+                    headshot,
+                },
+                Event::Kill {
+                    attacker: killer,
+                    victim: killed,
+                    weapon,
+                    headshot: was_headshot,
+                    ..
+                },
+            ) => {
+                if !matches_option(attacker, killer)
+                    || !matches_option(victim, killed)
+                    || !matches_option(headshot, was_headshot)
+                {
+                    return None;
+                }
+                vars.insert("attacker".into(), killer.clone());
+                vars.insert("victim".into(), killed.clone());
+                vars.insert("weapon".into(), weapon.clone());
+            }
+            (
+                Trigger::Chat { scope, .. },
+                Event::Chat {
+                    sender,
+                    text,
+                    scope: chat_scope,
+                    ..
+                },
+            ) => {
+                if !matches_option(scope, chat_scope) {
+                    return None;
+                }
+                if let Some(pattern) = &self.sender {
+                    if !pattern.is_match(sender) {
+                        return None;
+                    }
+                }
+                if let Some(pattern) = &self.text {
+                    let captures = pattern.captures(text)?;
+                    for name in pattern.capture_names().flatten() {
+                        if let Some(value) = captures.name(name) {
+                            vars.insert(name.to_string(), value.as_str().to_string());
+                        }
+                    }
+                }
+                vars.insert("sender".into(), sender.clone());
+                vars.insert("text".into(), text.clone());
+            }
+            (Trigger::ConVar { name, value }, Event::ConVar(var_name, var_value)) => {
+                if name != var_name || !matches_option(value, var_value) {
+                    return None;
+                }
+                vars.insert("name".into(), var_name.clone());
+                vars.insert("value".into(), var_value.clone());
+            }
+            _ => return None,
+        }
+        Some(vars)
+    }
+}
+
+impl Conditions {
+    pub fn check(&self, state: &State) -> bool {
+        (self.map.is_none() || self.map == state.map)
+            && matches_option(&self.ui_state, &state.ui_state)
+            && self
+                .game_mode
+                .as_ref()
+                .is_none_or(|mode| mode.eq_ignore_ascii_case(&format!("{:?}", state.game_mode)))
+            && matches_option(&self.enabled, &state.enabled)
+            && self.connected.is_none_or(|connected| {
+                connected == state.status.is_variant(StatusDiscriminants::Connected)
+            })
+            && self.min_round.is_none_or(|round| state.round >= round)
+            && self.max_round.is_none_or(|round| state.round <= round)
+    }
+}
+
+fn matches_option<T: PartialEq>(filter: &Option<T>, value: &T) -> bool {
+    filter.as_ref().is_none_or(|filter| filter == value)
+}
+
+/// Replace `{name}` in `template`, unknown names are left as they are. Values come from other
+/// players so `escape` is used to stop them running extra commands or breaking out of a JSON
+/// string.
+fn render(template: &str, vars: &HashMap<String, String>, escape: fn(&str) -> String) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match rest
+            .find('}')
+            .and_then(|end| Some((vars.get(&rest[1..end])?, end)))
+        {
+            Some((value, end)) => {
+                output.push_str(&escape(value));
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Escape `value` to go inside a JSON string.
+fn escape_json(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Declarative automation, rules are matched against every event and return the actions to run.
+#[derive(Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    pub fn new(rules: Vec<RuleConfig>) -> GenericResult<Self> {
+        Ok(Self {
+            rules: rules
+                .into_iter()
+                .map(Rule::new)
+                .collect::<GenericResult<_>>()?,
+        })
+    }
+
+    /// Load rules from a TOML file of `[[rule]]` tables.
+    pub fn load(path: &Path) -> GenericResult<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(contents: &str) -> GenericResult<Self> {
+        let file: RulesFile = toml::from_str(contents)?;
+        Self::new(file.rules)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The actions of every rule triggered by `event`, with templates filled in. `state` should
+    /// already have been updated with the event.
+    pub fn evaluate(&mut self, event: &Event, state: &State) -> Vec<Action> {
+        let mut actions = Vec::new();
+        let now = Instant::now();
+        for rule in &mut self.rules {
+            let mut vars = match rule.trigger(event) {
+                Some(vars) => vars,
+                None => continue,
+            };
+            if !rule.config.conditions.check(state) {
+                continue;
+            }
+            let debounce = Duration::from_millis(rule.config.debounce);
+            if let Some(last_fired) = rule.last_fired {
+                if now.duration_since(last_fired) < debounce {
+                    log::debug!("Rule {:?} debounced", rule.config.name);
+                    continue;
+                }
+            }
+            rule.last_fired = Some(now);
+            log::debug!("Rule {:?} fired", rule.config.name);
+
+            vars.insert("round".into(), state.round.to_string());
+            if let Some(map) = &state.map {
+                vars.entry("map".into()).or_insert_with(|| map.clone());
+            }
+            actions.extend(rule.config.actions.iter().map(|action| match action {
+                Action::Command(command) => {
+                    Action::Command(render(command, &vars, sanitize_argument))
+                }
+                Action::State(state_action) => Action::State(*state_action),
+                Action::Webhook { url, body } => Action::Webhook {
+                    url: url.clone(),
+                    body: Some(match body {
+                        Some(body) => render(body, &vars, escape_json),
+                        None => json!({ "rule": rule.config.name, "event": event }).to_string(),
+                    }),
+                },
+            }));
+        }
+        actions
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use async_trait::async_trait;
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Map, AST};
+
+use crate::listener::{Context, Listener};
+use crate::types::{Event, GenericResult, State};
+
+/// How often to check if the script file has changed
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Limits so a broken script cannot hang the listener
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 4096;
+const MAX_ARRAY_SIZE: usize = 1024;
+const MAX_MAP_SIZE: usize = 1024;
+
+/// Runs a [Rhai](https://rhai.rs) script, reloading it whenever the file changes.
+///
+/// Scripts can define any of these functions, `event` and `state` are the JSON forms of
+/// [`Event`] and [`State`] and changing them has no effect. The `state` passed to `on_event` is
+/// from before the event was applied:
+///
+/// ```rhai
+/// fn on_load() { this.kills = 0; }
+/// fn on_connect() { send("echo script connected"); }
+/// fn on_event(event, state) {
+///     if event.type == "Kill" && event.data.attacker == state.player_name {
+///         this.kills += 1;
+///         send(`say ${this.kills} kills`);
+///     }
+/// }
+/// fn on_state_change(state) {}
+/// ```
+///
+/// `this` is a map that keeps the script's variables between calls and reloads. Scripts have no
+/// file or network access, they can only `send(command)` to the console and `print` to the log.
+pub struct ScriptListener {
+    path: PathBuf,
+    engine: Engine,
+    ast: AST,
+    vars: Dynamic,
+    /// The last state as a script value, events arrive before the state they change
+    last_state: Dynamic,
+    commands: Arc<Mutex<Vec<String>>>,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl ScriptListener {
+    pub fn load(path: &Path) -> GenericResult<Self> {
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(MAX_CALL_LEVELS)
+            .set_max_string_size(MAX_STRING_SIZE)
+            .set_max_array_size(MAX_ARRAY_SIZE)
+            .set_max_map_size(MAX_MAP_SIZE);
+        {
+            let commands = commands.clone();
+            engine.register_fn("send", move |command: &str| {
+                commands.lock().unwrap().push(command.to_string());
+            });
+        }
+        let name = path.display().to_string();
+        engine.on_print(move |text| log::info!("[{}] {}", name, text));
+        let name = path.display().to_string();
+        engine.on_debug(move |text, _, pos| log::debug!("[{}] {:?} {}", name, pos, text));
+
+        let ast = engine.compile_file(path.to_path_buf())?;
+        let mut script = Self {
+            path: path.to_path_buf(),
+            engine,
+            ast,
+            vars: Dynamic::from_map(Map::new()),
+            last_state: to_dynamic(&State::default()),
+            commands,
+            modified: modified(path),
+            last_check: Instant::now(),
+        };
+        script.call("on_load", ());
+        Ok(script)
+    }
+
+    fn reload_if_changed(&mut self) {
+        if self.last_check.elapsed() < RELOAD_INTERVAL {
+            return;
+        }
+        self.last_check = Instant::now();
+
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+        // Keep running the old script if the new one does not compile
+        match self.engine.compile_file(self.path.clone()) {
+            Ok(ast) => {
+                log::info!("Reloaded script {:?}", self.path);
+                self.ast = ast;
+                self.call("on_load", ());
+            }
+            Err(e) => log::error!("Error reloading script {:?} {}", self.path, e),
+        }
+    }
+
+    fn call(&mut self, name: &str, args: impl FuncArgs) {
+        if !self.ast.iter_functions().any(|f| f.name == name) {
+            return;
+        }
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.vars);
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut rhai::Scope::new(),
+            &self.ast,
+            name,
+            args,
+        );
+        if let Err(e) = result {
+            log::error!("Error in {} of {:?} {}", name, self.path, e);
+        }
+    }
+
+    fn flush(&self, ctx: &Context) {
+        for command in self.commands.lock().unwrap().drain(..) {
+            if let Err(e) = ctx.queue(command.as_str()) {
+                log::error!("Error queueing script command {:?} {}", command, e);
+            }
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn to_dynamic<T: serde::Serialize>(value: &T) -> Dynamic {
+    rhai::serde::to_dynamic(value).unwrap_or(Dynamic::UNIT)
+}
+
+#[async_trait]
+impl Listener for ScriptListener {
+    async fn on_connect(&mut self, ctx: &Context) {
+        self.call("on_connect", ());
+        self.flush(ctx);
+    }
+
+    async fn on_event(&mut self, ctx: &Context, event: &Event) {
+        self.reload_if_changed();
+        let state = self.last_state.clone();
+        self.call("on_event", (to_dynamic(event), state));
+        self.flush(ctx);
+    }
+
+    async fn on_state_change(&mut self, ctx: &Context, state: &State) {
+        self.last_state = to_dynamic(state);
+        self.call("on_state_change", (self.last_state.clone(),));
+        self.flush(ctx);
+    }
+}
@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use regex::Regex;
+use tokio::sync::broadcast;
+use tokio::time::timeout;
+
+use csgo_netcon::config::Config;
+use csgo_netcon::{ClientOptions, NetconClient};
+
+/// Exit codes of `netcontool send`
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_CONNECTION: i32 = 1;
+pub const EXIT_TIMEOUT: i32 = 2;
+
+/// Send a command once, optionally waiting for a line of output matching `wait`.
+///
+/// Returns the exit code for the process.
+pub async fn send(config: &Config, command: &str, wait: Option<&Regex>, duration: Duration) -> i32 {
+    // Only the user's command should run, and --wait should only see its output
+    let options = ClientOptions {
+        resync: false,
+        ..ClientOptions::default()
+    };
+    let client = match config.addr() {
+        Ok(addr) => match NetconClient::connect_with(addr, options).await {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Could not connect to {}: {}", addr, e);
+                return EXIT_CONNECTION;
+            }
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_CONNECTION;
+        }
+    };
+
+    // Subscribe before sending so no output can be missed
+    let mut lines = client.lines();
+    if let Err(e) = client.send_command(command).await {
+        eprintln!("Could not send {:?}: {}", command, e);
+        return EXIT_CONNECTION;
+    }
+
+    let pattern = match wait {
+        Some(pattern) => pattern,
+        None => return EXIT_OK,
+    };
+    let found = timeout(duration, async {
+        loop {
+            match lines.recv().await {
+                Ok(line) if pattern.is_match(&line) => return Some(line),
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+    .await;
+
+    match found {
+        Ok(Some(line)) => {
+            println!("{}", line.trim());
+            EXIT_OK
+        }
+        Ok(None) => {
+            eprintln!(
+                "Connection closed before output matched {:?}",
+                pattern.as_str()
+            );
+            EXIT_CONNECTION
+        }
+        Err(_) => {
+            eprintln!(
+                "No output matched {:?} within {:?}",
+                pattern.as_str(),
+                duration
+            );
+            EXIT_TIMEOUT
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::path::Path;
+use std::time::Duration;
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context as LineContext, Editor, Helper};
+
+use csgo_netcon::config::Config;
+use csgo_netcon::cvars::Cvars;
+use csgo_netcon::types::GenericResult;
+use csgo_netcon::NetconClient;
+
+/// `cvarlist` prints a few thousand lines so give it longer than other queries
+const CVARLIST_TIMEOUT: Duration = Duration::from_secs(15);
+
+const PROMPT: &str = "] ";
+
+struct ShellHelper {
+    cvars: Cvars,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &LineContext<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        // Only complete the first word of each command
+        let start = line[..pos].rfind(';').map(|i| i + 1).unwrap_or(0);
+        let word = line[start..pos].trim_start();
+        if word.contains(' ') {
+            return Ok((pos, Vec::new()));
+        }
+        Ok((pos - word.len(), self.cvars.complete(word)))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+/// Read commands from the terminal, send them to the console and print their output.
+pub async fn shell(config: &Config, history: &Path) -> GenericResult<()> {
+    let client = NetconClient::connect_retry(config.addr()?, Duration::from_secs(1)).await;
+
+    let cvars = Cvars::default();
+    {
+        let cvars = cvars.clone();
+        let events = client.events();
+        tokio::spawn(async move {
+            while let Ok(event) = events.recv().await {
+                cvars.learn(&event);
+            }
+        });
+    }
+    match cvars.load_cvarlist(&client, CVARLIST_TIMEOUT).await {
+        Ok(learned) => log::info!("Learned {} cvars", learned),
+        Err(e) => log::warn!("Could not list cvars for completion {}", e),
+    }
+
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(ShellHelper { cvars }));
+    if editor.load_history(history).is_err() {
+        log::debug!("No history loaded from {:?}", history);
+    }
+
+    loop {
+        // The editor blocks on the terminal so read on another thread
+        let (returned, line) = tokio::task::spawn_blocking(move || {
+            let line = editor.readline(PROMPT);
+            (editor, line)
+        })
+        .await?;
+        editor = returned;
+
+        let line = match line {
+            Ok(line) => line,
+            // Ctrl+C clears the line like a normal shell
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let command = line.trim();
+        if command.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(command)?;
+
+        match client.query(command).await {
+            Ok(output) => {
+                for line in output {
+                    println!("{}", line);
+                }
+            }
+            Err(e) => eprintln!("Error running {:?}: {}", command, e),
+        }
+    }
+
+    editor.save_history(history)?;
+    Ok(())
+}
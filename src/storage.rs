@@ -0,0 +1,433 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::listener::{Context, Listener};
+use crate::types::round::unix_millis;
+use crate::types::{
+    Damage, DamageDirection, Event, GenericResult, HostType, Player, RoundRecord, State, Status,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS matches (
+    id INTEGER PRIMARY KEY,
+    map TEXT NOT NULL,
+    game_type TEXT,
+    game_mode TEXT,
+    hostname TEXT,
+    host_type TEXT,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS rounds (
+    match_id INTEGER NOT NULL REFERENCES matches(id),
+    number INTEGER NOT NULL,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER,
+    damage_given INTEGER NOT NULL,
+    damage_taken INTEGER NOT NULL,
+    hits_given INTEGER NOT NULL,
+    hits_taken INTEGER NOT NULL,
+    kills INTEGER NOT NULL,
+    PRIMARY KEY (match_id, number)
+);
+CREATE TABLE IF NOT EXISTS damage (
+    id INTEGER PRIMARY KEY,
+    match_id INTEGER NOT NULL REFERENCES matches(id),
+    round INTEGER NOT NULL,
+    direction TEXT NOT NULL,
+    target TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    hits INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    UNIQUE (match_id, round, direction, target)
+);
+CREATE TABLE IF NOT EXISTS players (
+    match_id INTEGER NOT NULL REFERENCES matches(id),
+    steam_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (match_id, steam_id)
+);
+";
+
+/// Server details stored with each match
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MatchInfo {
+    pub game_type: String,
+    pub game_mode: String,
+    pub hostname: Option<String>,
+    pub host_type: Option<String>,
+}
+
+impl MatchInfo {
+    pub fn from_state(state: &State) -> Self {
+        let (hostname, host_type) = match &state.status {
+            Status::Connected(status) => (
+                Some(status.hostname.clone()),
+                Some(match &status.host_type {
+                    HostType::Official(location) => format!("Official {}", location),
+                    HostType::Unofficial => String::from("Unofficial"),
+                }),
+            ),
+            Status::NotConnected => (None, None),
+        };
+        Self {
+            game_type: format!("{:?}", state.game_type),
+            game_mode: format!("{:?}", state.game_mode),
+            hostname,
+            host_type,
+        }
+    }
+}
+
+/// Which matches to read back, every field is optional.
+#[derive(Debug, Clone, Default)]
+pub struct MatchFilter {
+    pub map: Option<String>,
+    /// Name of the [`crate::types::GameMode`], compared case insensitively
+    pub mode: Option<String>,
+    /// Only the most recent matches
+    pub limit: Option<usize>,
+}
+
+/// A stored match with totals from its rounds
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatchSummary {
+    pub id: i64,
+    pub map: String,
+    pub game_mode: Option<String>,
+    pub hostname: Option<String>,
+    pub started_at: u64,
+    pub ended_at: Option<u64>,
+    pub rounds: u32,
+    pub damage_given: u64,
+    pub damage_taken: u64,
+    pub kills: u32,
+    pub adr: f64,
+}
+
+/// Totals over a set of matches
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MatchStats {
+    pub matches: u32,
+    pub rounds: u32,
+    pub damage_given: u64,
+    pub damage_taken: u64,
+    pub kills: u32,
+    pub adr: f64,
+    /// Average damage taken per round
+    pub average_damage_taken: f64,
+}
+
+impl MatchStats {
+    pub fn from_matches(matches: &[MatchSummary]) -> Self {
+        let mut stats = matches.iter().fold(Self::default(), |mut stats, summary| {
+            stats.matches += 1;
+            stats.rounds += summary.rounds;
+            stats.damage_given += summary.damage_given;
+            stats.damage_taken += summary.damage_taken;
+            stats.kills += summary.kills;
+            stats
+        });
+        if stats.rounds > 0 {
+            stats.adr = stats.damage_given as f64 / stats.rounds as f64;
+            stats.average_damage_taken = stats.damage_taken as f64 / stats.rounds as f64;
+        }
+        stats
+    }
+}
+
+/// Long term match history in a SQLite database.
+pub struct Storage {
+    conn: Connection,
+}
+
+impl Storage {
+    pub fn open(path: &Path) -> GenericResult<Self> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> GenericResult<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> GenericResult<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Matches matching `filter`, most recent first.
+    pub fn matches(&self, filter: &MatchFilter) -> GenericResult<Vec<MatchSummary>> {
+        let mut statement = self.conn.prepare(
+            "SELECT m.id, m.map, m.game_mode, m.hostname, m.started_at, m.ended_at,
+                COUNT(r.number), COALESCE(SUM(r.damage_given), 0),
+                COALESCE(SUM(r.damage_taken), 0), COALESCE(SUM(r.kills), 0)
+                FROM matches m LEFT JOIN rounds r ON r.match_id = m.id
+                WHERE (?1 IS NULL OR m.map = ?1)
+                    AND (?2 IS NULL OR LOWER(m.game_mode) = LOWER(?2))
+                GROUP BY m.id
+                ORDER BY m.started_at DESC, m.id DESC
+                LIMIT ?3",
+        )?;
+        // A negative limit means no limit in SQLite
+        let limit = filter.limit.map(|limit| limit as i64).unwrap_or(-1);
+        let matches = statement
+            .query_map(params![filter.map, filter.mode, limit], |row| {
+                let rounds: u32 = row.get(6)?;
+                let damage_given: u64 = row.get(7)?;
+                Ok(MatchSummary {
+                    id: row.get(0)?,
+                    map: row.get(1)?,
+                    game_mode: row.get(2)?,
+                    hostname: row.get(3)?,
+                    started_at: row.get(4)?,
+                    ended_at: row.get(5)?,
+                    rounds,
+                    damage_given,
+                    damage_taken: row.get(8)?,
+                    kills: row.get(9)?,
+                    adr: if rounds > 0 {
+                        damage_given as f64 / rounds as f64
+                    } else {
+                        0.0
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(matches)
+    }
+
+    pub fn stats(&self, filter: &MatchFilter) -> GenericResult<MatchStats> {
+        Ok(MatchStats::from_matches(&self.matches(filter)?))
+    }
+
+    pub fn begin_match(&self, map: &str, started_at: u64) -> GenericResult<i64> {
+        self.conn.execute(
+            "INSERT INTO matches (map, started_at) VALUES (?1, ?2)",
+            params![map, started_at],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_match(&self, match_id: i64, info: &MatchInfo) -> GenericResult<()> {
+        self.conn.execute(
+            "UPDATE matches SET game_type = ?2, game_mode = ?3, hostname = ?4, host_type = ?5
+                WHERE id = ?1",
+            params![
+                match_id,
+                info.game_type,
+                info.game_mode,
+                info.hostname,
+                info.host_type
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn end_match(&self, match_id: i64, ended_at: u64) -> GenericResult<()> {
+        self.conn.execute(
+            "UPDATE matches SET ended_at = ?2 WHERE id = ?1",
+            params![match_id, ended_at],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_round(&self, match_id: i64, round: &RoundRecord) -> GenericResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO rounds
+                (match_id, number, started_at, ended_at, damage_given, damage_taken,
+                 hits_given, hits_taken, kills)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                match_id,
+                round.number,
+                round.start,
+                round.end,
+                round.damage_given(),
+                round.damage_taken(),
+                round.hits_given(),
+                round.hits_taken(),
+                round.lethal_given(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Damage lines are totals for the round, so they replace the row for the same target.
+    pub fn record_damage(&self, match_id: i64, round: u8, damage: &Damage) -> GenericResult<()> {
+        let direction = match damage.direction {
+            DamageDirection::Given => "given",
+            DamageDirection::Taken => "taken",
+        };
+        self.conn.execute(
+            "INSERT OR REPLACE INTO damage
+                (match_id, round, direction, target, amount, hits, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                match_id,
+                round,
+                direction,
+                damage.target,
+                damage.amount,
+                damage.hits,
+                unix_millis()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_players(&self, match_id: i64, players: &[Player]) -> GenericResult<()> {
+        let mut statement = self.conn.prepare_cached(
+            "INSERT OR REPLACE INTO players (match_id, steam_id, name) VALUES (?1, ?2, ?3)",
+        )?;
+        for player in players {
+            statement.execute(params![match_id, player.steam_id, player.name])?;
+        }
+        Ok(())
+    }
+}
+
+struct CurrentMatch {
+    id: i64,
+    map: String,
+    info: MatchInfo,
+    /// Rounds before this index have finished and been written
+    stored_rounds: usize,
+    round: u8,
+}
+
+/// Records every match played to a [`Storage`].
+///
+/// A match starts on a map change and ends when the map is cleared, e.g. on returning to the
+/// main menu or disconnecting. Starting the rounds again on the same map, e.g. with the `start`
+/// command after warmup, also starts a new match.
+pub struct StorageListener {
+    storage: Storage,
+    current: Option<CurrentMatch>,
+    /// Rounds of the current match from the last state, written when the match ends
+    rounds: Vec<RoundRecord>,
+}
+
+impl StorageListener {
+    pub fn new(storage: Storage) -> Self {
+        Self {
+            storage,
+            current: None,
+            rounds: Vec::new(),
+        }
+    }
+
+    fn begin(&mut self, map: &str) -> GenericResult<()> {
+        self.end()?;
+        let id = self.storage.begin_match(map, unix_millis())?;
+        log::debug!("Recording match {} on {}", id, map);
+        self.current = Some(CurrentMatch {
+            id,
+            map: map.to_string(),
+            info: MatchInfo::default(),
+            stored_rounds: 0,
+            round: 0,
+        });
+        Ok(())
+    }
+
+    fn end(&mut self) -> GenericResult<()> {
+        if let Some(current) = self.current.take() {
+            for round in self.rounds.iter().skip(current.stored_rounds) {
+                let mut round = round.clone();
+                round.finish();
+                self.storage.record_round(current.id, &round)?;
+            }
+            self.storage.end_match(current.id, unix_millis())?;
+        }
+        self.rounds.clear();
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> GenericResult<()> {
+        match event {
+            Event::MapChange(map) => self.begin(map)?,
+            Event::Damage(damage) => {
+                if let Some(current) = &self.current {
+                    self.storage
+                        .record_damage(current.id, current.round, damage)?;
+                }
+            }
+            Event::Status(Status::Connected(status)) => {
+                if let Some(current) = &self.current {
+                    self.storage
+                        .record_players(current.id, &status.player_list)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Whether the rounds of `state` were cleared since the last state without a map change.
+    fn restarted(&self, state: &State) -> bool {
+        let went_back = match (state.rounds.first(), self.rounds.first()) {
+            (Some(first), Some(last_first)) => first.number < last_first.number,
+            _ => false,
+        };
+        state.rounds.len() < self.rounds.len() || went_back
+    }
+
+    fn handle_state(&mut self, state: &State) -> GenericResult<()> {
+        match (&state.map, &self.current) {
+            (None, Some(_)) => return self.end(),
+            (Some(map), None) => self.begin(map)?,
+            (Some(map), Some(current)) if *map != current.map => self.begin(map)?,
+            (Some(map), Some(_)) if self.restarted(state) => self.begin(map)?,
+            _ => {}
+        }
+        let current = match &mut self.current {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+
+        let info = MatchInfo::from_state(state);
+        if info != current.info {
+            self.storage.update_match(current.id, &info)?;
+            current.info = info;
+        }
+
+        current.round = state.round;
+        for round in state.rounds.iter().skip(current.stored_rounds) {
+            if round.end.is_none() {
+                break;
+            }
+            self.storage.record_round(current.id, round)?;
+            current.stored_rounds += 1;
+        }
+        self.rounds = state.rounds.clone();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Listener for StorageListener {
+    async fn on_event(&mut self, _ctx: &Context, event: &Event) {
+        if let Err(e) = self.handle_event(event) {
+            log::error!("Error storing event {}", e);
+        }
+    }
+
+    async fn on_state_change(&mut self, _ctx: &Context, state: &State) {
+        if let Err(e) = self.handle_state(state) {
+            log::error!("Error storing state {}", e);
+        }
+    }
+
+    async fn on_shutdown(&mut self, _ctx: &Context) {
+        if let Err(e) = self.end() {
+            log::error!("Error storing match {}", e);
+        }
+    }
+}
@@ -1,12 +1,20 @@
 use tokio::io::AsyncRead;
 
 use crate::reader::LineReader;
-use crate::types::{Damage, Event, GenericResult, Status, StatusData};
+use crate::types::{
+    diff_players, parse_chat, parse_damage_header, parse_kill, Damage, DamageDirection,
+    DamageReport, Event, GenericResult, Player, Status, StatusData, DAMAGE_SEPARATOR,
+};
 
 pub async fn stream_reader<T: AsyncRead + Send>(
     mut line_reader: LineReader<T>,
     chan: async_channel::Sender<Event>,
 ) -> GenericResult<()> {
+    // The damage block has no end marker so it is sent on the first line after it
+    let mut report: Option<DamageReport> = None;
+    // Hostname and players from the last `status`, joins and leaves are relative to it
+    let mut players: Option<(String, Vec<Player>)> = None;
+
     loop {
         let line = line_reader.read_line().await?;
         let line = line.trim();
@@ -15,7 +23,37 @@ pub async fn stream_reader<T: AsyncRead + Send>(
             continue;
         }
 
-        println!(": {:?}", line);
+        log::trace!(": {:?}", line);
+
+        if line == DAMAGE_SEPARATOR {
+            continue;
+        }
+
+        if let Some((player, direction)) = parse_damage_header(line) {
+            // Given always comes first so it starts a new block
+            if direction == DamageDirection::Given || report.is_none() {
+                if let Some(report) = report.take() {
+                    chan.send(Event::DamageReport(report)).await?;
+                }
+                report = Some(DamageReport::new(player));
+            }
+            continue;
+        }
+
+        if let Ok(damage) = Damage::try_from(line) {
+            if let Some(report) = &mut report {
+                report.push(damage.clone());
+            }
+            chan.send(Event::Damage(damage)).await?;
+            continue;
+        } else if line.starts_with("Damage") {
+            // FIXME: Ignore
+            log::debug!("{:?} {:?}", line, Damage::try_from(line));
+        }
+
+        if let Some(report) = report.take() {
+            chan.send(Event::DamageReport(report)).await?;
+        }
 
         if let Some(data) = line.strip_prefix("ChangeGameUIState:") {
             let mut parts = data.split("->");
@@ -29,7 +67,7 @@ pub async fn stream_reader<T: AsyncRead + Send>(
                         .await?;
                 }
                 _ => {
-                    eprintln!("Error {:?}", state);
+                    log::warn!("Error {:?}", state);
                 }
             }
             continue;
@@ -52,24 +90,34 @@ pub async fn stream_reader<T: AsyncRead + Send>(
         }
 
         if line == "Not connected to server" {
+            players = None;
             chan.send(Event::Status(Status::NotConnected)).await?;
             continue;
         }
 
         if let Some(hostname) = line.strip_prefix("hostname: ") {
             match StatusData::parse(hostname.to_string(), &mut line_reader).await {
-                Ok(status) => chan.send(Event::Status(Status::Connected(status))).await?,
-                Err(e) => eprintln!("Error parsing status {:?}", e),
+                Ok(status) => {
+                    let changes = match &players {
+                        Some((hostname, players)) if *hostname == status.hostname => {
+                            diff_players(players, &status.player_list)
+                        }
+                        _ => Vec::new(),
+                    };
+                    players = Some((status.hostname.clone(), status.player_list.clone()));
+                    chan.send(Event::Status(Status::Connected(status))).await?;
+                    for change in changes {
+                        chan.send(change).await?;
+                    }
+                }
+                Err(e) => log::warn!("Error parsing status {:?}", e),
             }
             continue;
         }
 
-        if let Ok(damage) = Damage::try_from(line) {
-            chan.send(Event::Damage(damage)).await?;
+        if let Ok(kill) = parse_kill(line) {
+            chan.send(kill).await?;
             continue;
-        } else if line.starts_with("Damage") {
-            // FIXME: Ignore
-            eprintln!("{:?} {:?}", line, Damage::try_from(line));
         }
 
         if let Some(line) = line.strip_prefix("\"") {
@@ -102,6 +150,11 @@ pub async fn stream_reader<T: AsyncRead + Send>(
 
         if let Some(command) = line.strip_prefix("??? ") {
             chan.send(Event::Command(command.to_string())).await?;
+            continue;
+        }
+
+        if let Ok(chat) = parse_chat(line) {
+            chan.send(chat).await?;
         }
     }
 }
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+use super::Event;
+
+/// Invisible left-to-right marks CS:GO wraps player names with in the console
+const LEFT_TO_RIGHT_MARK: char = '\u{200E}';
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Team {
+    Terrorist,
+    CounterTerrorist,
+    Spectator,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChatScope {
+    All,
+    Team,
+}
+
+/// Parse a chat line into an [`Event::Chat`], e.g.
+///
+/// * `Name : msg`
+/// * `*DEAD* Name : msg`
+/// * `(Counter-Terrorist) Name @ Location : msg`
+/// * `*DEAD*(Terrorist) Name : msg`
+pub fn parse_chat(line: &str) -> Result<Event, &'static str> {
+    let line = line.trim_start_matches(LEFT_TO_RIGHT_MARK);
+
+    let (dead, spectator, line) = if let Some(line) = line.strip_prefix("*DEAD*") {
+        (true, false, line)
+    } else if let Some(line) = line.strip_prefix("*SPEC*") {
+        (false, true, line)
+    } else {
+        (false, false, line)
+    };
+    let line = line.trim_start_matches([' ', LEFT_TO_RIGHT_MARK]);
+
+    let (team, scope, line) = if let Some(line) = line.strip_prefix("(Counter-Terrorist)") {
+        (Some(Team::CounterTerrorist), ChatScope::Team, line)
+    } else if let Some(line) = line.strip_prefix("(Terrorist)") {
+        (Some(Team::Terrorist), ChatScope::Team, line)
+    } else if let Some(line) = line.strip_prefix("(Spectator)") {
+        (Some(Team::Spectator), ChatScope::Team, line)
+    } else if spectator {
+        (Some(Team::Spectator), ChatScope::All, line)
+    } else {
+        (None, ChatScope::All, line)
+    };
+
+    let (sender, text) = line.split_once(" : ").ok_or("Invalid chat string 1")?;
+
+    // Unprefixed lines are ambiguous with other console output such as cvarlist
+    // (`name : value : flags : description`), which has more separators than a message would
+    if !dead && !spectator && team.is_none() && text.contains(" : ") {
+        Err("Invalid chat string 2")?
+    }
+
+    let (sender, location) = match (&scope, sender.rsplit_once(" @ ")) {
+        (ChatScope::Team, Some((sender, location))) => (sender, Some(clean(location))),
+        _ => (sender, None),
+    };
+
+    let sender = clean(sender);
+    if sender.is_empty() || sender.contains('"') {
+        Err("Invalid chat sender")?
+    }
+
+    Ok(Event::Chat {
+        sender,
+        team,
+        dead,
+        location,
+        text: clean(text),
+        scope,
+    })
+}
+
+fn clean(value: &str) -> String {
+    value
+        .trim_matches(|c: char| c.is_whitespace() || c == LEFT_TO_RIGHT_MARK)
+        .to_string()
+}
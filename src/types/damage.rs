@@ -1,10 +1,12 @@
-#[derive(Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DamageDirection {
     Given,
     Taken,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Damage {
     pub direction: DamageDirection,
     pub target: String,
@@ -49,3 +51,43 @@ impl TryFrom<&str> for Damage {
         })
     }
 }
+
+/// Header printed before each section of the damage block, e.g.
+/// `Player: Name - Damage Given`. The name is the local player.
+pub fn parse_damage_header(line: &str) -> Option<(&str, DamageDirection)> {
+    let line = line.strip_prefix("Player: ")?;
+    if let Some(player) = line.strip_suffix(" - Damage Given") {
+        Some((player, DamageDirection::Given))
+    } else if let Some(player) = line.strip_suffix(" - Damage Taken") {
+        Some((player, DamageDirection::Taken))
+    } else {
+        None
+    }
+}
+
+pub const DAMAGE_SEPARATOR: &str = "-------------------------";
+
+/// A complete damage block as printed on death or at the end of a round,
+/// each entry is the total for that opponent this round.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DamageReport {
+    pub player: String,
+    pub given: Vec<Damage>,
+    pub taken: Vec<Damage>,
+}
+
+impl DamageReport {
+    pub fn new(player: &str) -> Self {
+        Self {
+            player: player.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn push(&mut self, damage: Damage) {
+        match damage.direction {
+            DamageDirection::Given => self.given.push(damage),
+            DamageDirection::Taken => self.taken.push(damage),
+        }
+    }
+}
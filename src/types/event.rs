@@ -1,17 +1,46 @@
+use serde::Serialize;
 use strum::EnumDiscriminants;
 
-use super::{Damage, Status, UIState};
+use super::{ChatScope, Damage, DamageReport, Player, Status, Team, UIState};
 
-#[derive(Debug, EnumDiscriminants, PartialEq)]
+#[derive(Debug, Clone, EnumDiscriminants, PartialEq, Serialize)]
+#[serde(tag = "type", content = "data")]
 pub enum Event {
+    Connected,
+    Disconnected,
     Command(String),
     ChangeUIState(UIState, UIState),
     Damage(Damage),
+    DamageReport(DamageReport),
     MapChange(String),
     PlayerConnected(String),
+    /// A player in the latest `status` output who was not in the one before
+    PlayerJoined(Player),
+    /// A player missing from the latest `status` output
+    PlayerLeft(Player),
+    PlayerRenamed {
+        steam_id: String,
+        old_name: String,
+        new_name: String,
+    },
     EnterBuyPeriod,
     Status(Status),
     ConVar(String, String),
+    Chat {
+        sender: String,
+        team: Option<Team>,
+        dead: bool,
+        location: Option<String>,
+        text: String,
+        scope: ChatScope,
+    },
+    Kill {
+        attacker: String,
+        victim: String,
+        weapon: String,
+        headshot: bool,
+        assister: Option<String>,
+    },
     Tick(u8),
 }
 
@@ -1,3 +1,5 @@
+use serde::Serialize;
+
 macro_rules! valued_enum {
     (
         $(#[$meta:meta])*
@@ -28,7 +30,7 @@ macro_rules! valued_enum {
 }
 
 valued_enum!(
-    #[derive(Debug, Clone)]
+    #[derive(Debug, Clone, Serialize)]
     pub enum GameType (u8) {
     Classic = 0,
     GunGame = 1,
@@ -41,7 +43,7 @@ valued_enum!(
 );
 
 valued_enum!(
-    #[derive(Debug, Clone)]
+    #[derive(Debug, Clone, Serialize)]
     pub enum GameMode ((GameType, u8)) {
         Casual = (GameType::Classic, 0),
         Competitive = (GameType::Classic, 1),
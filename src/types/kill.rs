@@ -0,0 +1,58 @@
+use super::Event;
+
+/// Parse a kill feed line into an [`Event::Kill`].
+///
+/// Both the console form (`X killed Y with ak47 (headshot)`, `X + Z killed Y with ak47`) and the
+/// server log form (`"X<2><STEAM_1:0:1><CT>" [0 0 0] killed "Y<3><BOT><TERRORIST>" [0 0 0] with
+/// "ak47" (headshot)`) are supported.
+pub fn parse_kill(line: &str) -> Result<Event, &'static str> {
+    // Chat messages can contain anything, e.g. `Name : he killed me with an awp`
+    if line.contains(" : ") {
+        Err("Invalid kill string 0")?
+    }
+
+    let (attacker, rest) = line.split_once(" killed ").ok_or("Invalid kill string 1")?;
+    let (victim, rest) = rest.rsplit_once(" with ").ok_or("Invalid kill string 2")?;
+
+    let rest = rest.trim().trim_end_matches('.');
+    let (weapon, headshot) = match rest.strip_suffix("(headshot)") {
+        Some(weapon) => (weapon.trim(), true),
+        None => (rest, false),
+    };
+    let weapon = weapon.trim_matches('"');
+
+    let (attacker, assister) = match attacker.split_once(" + ") {
+        Some((attacker, assister)) => (attacker, Some(player_name(assister))),
+        None => (attacker, None),
+    };
+    let (attacker, victim) = (player_name(attacker), player_name(victim));
+
+    if attacker.is_empty() || victim.is_empty() || weapon.is_empty() || weapon.contains(' ') {
+        Err("Invalid kill string 3")?
+    }
+
+    Ok(Event::Kill {
+        attacker,
+        victim,
+        weapon: weapon.to_string(),
+        headshot,
+        assister,
+    })
+}
+
+/// Strip log decoration from a player, `"Name<2><STEAM_1:0:1><CT>" [0 0 0]` becomes `Name`.
+fn player_name(player: &str) -> String {
+    let player = player.trim();
+    let player = match player.split_once('"') {
+        Some((_, quoted)) => match quoted.split_once('"') {
+            Some((quoted, _)) => quoted,
+            None => quoted,
+        },
+        None => player,
+    };
+    let player = match player.find('<') {
+        Some(index) if player.ends_with('>') => &player[..index],
+        _ => player,
+    };
+    player.trim().to_string()
+}
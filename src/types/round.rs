@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use super::{Damage, DamageDirection, DamageReport};
+
+/// Damage dealt to and received from a single player in a round.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TargetDamage {
+    pub given: u64,
+    pub hits_given: u32,
+    pub taken: u64,
+    pub hits_taken: u32,
+    /// The local player killed this player
+    pub lethal_given: bool,
+    /// This player killed the local player
+    pub lethal_taken: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoundKill {
+    pub attacker: String,
+    pub victim: String,
+    pub weapon: String,
+    pub headshot: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundRecord {
+    pub number: u8,
+    /// When the buy period started, in milliseconds since the unix epoch
+    pub start: u64,
+    /// When the next round started or the game ended, in milliseconds since the unix epoch
+    pub end: Option<u64>,
+    /// Damage by the name of the other player
+    pub damage: HashMap<String, TargetDamage>,
+    /// Kill feed for this round
+    pub kills: Vec<RoundKill>,
+}
+
+impl RoundRecord {
+    pub fn new(number: u8) -> Self {
+        Self {
+            number,
+            start: unix_millis(),
+            end: None,
+            damage: HashMap::new(),
+            kills: Vec::new(),
+        }
+    }
+
+    /// Damage lines are totals for the round so they replace any previous
+    /// value, this stops a block printed twice from being counted twice.
+    /// Returns the amount previously recorded against the target.
+    pub fn record_damage(&mut self, damage: &Damage) -> u64 {
+        let target = self.damage.entry(damage.target.clone()).or_default();
+        let (total, hits) = match damage.direction {
+            DamageDirection::Given => (&mut target.given, &mut target.hits_given),
+            DamageDirection::Taken => (&mut target.taken, &mut target.hits_taken),
+        };
+        *hits = damage.hits as u32;
+        std::mem::replace(total, damage.amount as u64)
+    }
+
+    pub fn record_kill(&mut self, kill: RoundKill, player: Option<&str>) {
+        if let Some(player) = player {
+            self.mark_lethal(&kill, player);
+        }
+        self.kills.push(kill);
+    }
+
+    pub fn record_report(&mut self, report: &DamageReport) {
+        for damage in report.given.iter().chain(report.taken.iter()) {
+            self.record_damage(damage);
+            // Nobody survives 100 damage so count it even if the kill feed was missed
+            if damage.amount >= 100 {
+                let target = self.damage.entry(damage.target.clone()).or_default();
+                match damage.direction {
+                    DamageDirection::Given => target.lethal_given = true,
+                    DamageDirection::Taken => target.lethal_taken = true,
+                }
+            }
+        }
+        for kill in self.kills.clone() {
+            self.mark_lethal(&kill, &report.player);
+        }
+    }
+
+    fn mark_lethal(&mut self, kill: &RoundKill, player: &str) {
+        if kill.attacker == player && kill.victim != player {
+            self.damage
+                .entry(kill.victim.clone())
+                .or_default()
+                .lethal_given = true;
+        } else if kill.victim == player && kill.attacker != player {
+            self.damage
+                .entry(kill.attacker.clone())
+                .or_default()
+                .lethal_taken = true;
+        }
+    }
+
+    pub fn finish(&mut self) {
+        if self.end.is_none() {
+            self.end = Some(unix_millis());
+        }
+    }
+
+    pub fn damage_given(&self) -> u64 {
+        self.damage.values().map(|target| target.given).sum()
+    }
+
+    pub fn damage_taken(&self) -> u64 {
+        self.damage.values().map(|target| target.taken).sum()
+    }
+
+    pub fn hits_given(&self) -> u32 {
+        self.damage.values().map(|target| target.hits_given).sum()
+    }
+
+    pub fn hits_taken(&self) -> u32 {
+        self.damage.values().map(|target| target.hits_taken).sum()
+    }
+
+    /// Number of opponents the local player killed
+    pub fn lethal_given(&self) -> usize {
+        self.damage
+            .values()
+            .filter(|target| target.lethal_given)
+            .count()
+    }
+}
+
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
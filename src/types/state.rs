@@ -1,8 +1,13 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
 use super::game_mode::{GameMode, GameType};
 use super::Status;
 use super::UIState;
+use super::{DamageDirection, Event, RoundKill, RoundRecord};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct State {
     pub ui_state: UIState,
     pub status: Status,
@@ -13,6 +18,35 @@ pub struct State {
     pub game_type: GameType,
     pub game_mode: GameMode,
     pub enabled: bool,
+    /// Kill feed stats for every player seen this game, by name
+    pub players: HashMap<String, PlayerStats>,
+    /// Every round played this game, the last round is the current round
+    pub rounds: Vec<RoundRecord>,
+    /// Name of the local player, learned from damage reports
+    pub player_name: Option<String>,
+    /// Damage before the first round started, e.g. after joining mid-match, so lines printed
+    /// again are only counted once
+    #[serde(skip)]
+    pub pre_round: RoundRecord,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PlayerStats {
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+    pub headshots: u32,
+}
+
+impl PlayerStats {
+    /// Percentage of kills that were headshots
+    pub fn headshot_percentage(&self) -> f64 {
+        if self.kills == 0 {
+            0.0
+        } else {
+            self.headshots as f64 * 100.0 / self.kills as f64
+        }
+    }
 }
 
 impl Default for State {
@@ -27,6 +61,10 @@ impl Default for State {
             game_type: GameType::Classic,
             game_mode: GameMode::Casual,
             enabled: false,
+            players: HashMap::new(),
+            rounds: Vec::new(),
+            player_name: None,
+            pre_round: RoundRecord::new(0),
         }
     }
 }
@@ -37,5 +75,162 @@ impl State {
         self.round = 0;
         self.total_damage_given = 0;
         self.total_damage_taken = 0;
+        self.players.clear();
+        self.rounds.clear();
+        self.pre_round = RoundRecord::new(0);
+    }
+
+    /// Finish the current round and start recording the next one.
+    pub fn start_round(&mut self) {
+        if let Some(round) = self.rounds.last_mut() {
+            round.finish();
+        }
+        self.round += 1;
+        self.rounds.push(RoundRecord::new(self.round));
+    }
+
+    pub fn current_round(&self) -> Option<&RoundRecord> {
+        self.rounds.last()
+    }
+
+    /// Average damage given per round this game
+    pub fn adr(&self) -> f64 {
+        if self.rounds.is_empty() {
+            0.0
+        } else {
+            self.rounds
+                .iter()
+                .map(|round| round.damage_given())
+                .sum::<u64>() as f64
+                / self.rounds.len() as f64
+        }
+    }
+
+    /// Average damage taken per round this game
+    pub fn average_damage_taken(&self) -> f64 {
+        if self.rounds.is_empty() {
+            0.0
+        } else {
+            self.rounds
+                .iter()
+                .map(|round| round.damage_taken())
+                .sum::<u64>() as f64
+                / self.rounds.len() as f64
+        }
+    }
+
+    /// Apply an event from the console to the state, returns true if the state changed.
+    pub fn update(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Disconnected => {
+                // Only forget what the resync queries again, a brief drop mid-match keeps the
+                // game data until a real map or status change
+                self.status = Status::NotConnected;
+                self.game_type = GameType::Classic;
+                self.game_mode = GameMode::Casual;
+                true
+            }
+            Event::ChangeUIState(_, new_state) => {
+                self.ui_state = new_state.clone();
+                if self.ui_state == UIState::MainMenu {
+                    self.clear_game_data(None);
+                }
+                true
+            }
+            Event::Status(new_status) => {
+                self.status = new_status.clone();
+                if let Status::Connected(data) = &self.status {
+                    self.map = Some(data.map.clone());
+                } else {
+                    // The game is at the main menu, e.g. it restarted while disconnected
+                    self.ui_state = UIState::MainMenu;
+                    self.clear_game_data(None);
+                }
+                true
+            }
+            Event::MapChange(map) => {
+                self.clear_game_data(Some(map.clone()));
+                true
+            }
+            Event::EnterBuyPeriod => {
+                self.start_round();
+                true
+            }
+            Event::Damage(damage) => {
+                // Only count what was added since the target's last line this round
+                let round = match self.rounds.last_mut() {
+                    Some(round) => round,
+                    None => &mut self.pre_round,
+                };
+                let previous = round.record_damage(damage);
+                let added = (damage.amount as u64).saturating_sub(previous);
+                if damage.direction == DamageDirection::Given {
+                    self.total_damage_given += added;
+                } else {
+                    self.total_damage_taken += added;
+                }
+                true
+            }
+            Event::DamageReport(report) => {
+                self.player_name = Some(report.player.clone());
+                if let Some(round) = self.rounds.last_mut() {
+                    round.record_report(report);
+                }
+                true
+            }
+            Event::Kill {
+                attacker,
+                victim,
+                weapon,
+                headshot,
+                assister,
+            } => {
+                if let Some(round) = self.rounds.last_mut() {
+                    round.record_kill(
+                        RoundKill {
+                            attacker: attacker.clone(),
+                            victim: victim.clone(),
+                            weapon: weapon.clone(),
+                            headshot: *headshot,
+                        },
+                        self.player_name.as_deref(),
+                    );
+                }
+                // Suicides and world damage only count as a death
+                if attacker != victim && attacker != "world" {
+                    let stats = self.players.entry(attacker.clone()).or_default();
+                    stats.kills += 1;
+                    if *headshot {
+                        stats.headshots += 1;
+                    }
+                }
+                self.players.entry(victim.clone()).or_default().deaths += 1;
+                if let Some(assister) = assister {
+                    self.players.entry(assister.clone()).or_default().assists += 1;
+                }
+                true
+            }
+            Event::ConVar(name, value) => {
+                if name == "game_type" {
+                    if let Ok(value) = u8::from_str_radix(value, 10) {
+                        if let Some(game_type) = GameType::try_from(value) {
+                            self.game_type = game_type;
+                            return true;
+                        }
+                    }
+                }
+                if name == "game_mode" {
+                    if let Ok(value) = u8::from_str_radix(value, 10) {
+                        if let Some(game_mode) = GameMode::try_from((self.game_type.clone(), value))
+                        {
+                            self.game_mode = game_mode;
+                            return true;
+                        }
+                    }
+                }
+                false
+            }
+            _ => false,
+        }
     }
 }
@@ -1,10 +1,14 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
 use strum::EnumDiscriminants;
 use tokio::io::{AsyncRead, AsyncReadExt};
 
-use super::GenericResult;
-use crate::LineReader;
+use super::{Event, GenericResult};
+use crate::reader::LineReader;
 
-#[derive(Debug, Clone, EnumDiscriminants)]
+#[derive(Debug, Clone, EnumDiscriminants, Serialize)]
+#[serde(tag = "type", content = "data")]
 pub enum Status {
     NotConnected,
     Connected(StatusData),
@@ -22,7 +26,7 @@ impl Status {
     }
 }
 
-#[derive(Debug, Clone, derive_builder::Builder)]
+#[derive(Debug, Clone, derive_builder::Builder, Serialize)]
 pub struct StatusData {
     pub hostname: String,
     pub host_type: HostType,
@@ -36,13 +40,13 @@ pub struct StatusData {
     pub player_list: Vec<Player>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum HostType {
     Official(String),
     Unofficial,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Players {
     pub humans: u32,
     pub bots: u32,
@@ -55,13 +59,51 @@ impl Players {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Player {
     pub id: String,
     pub name: String,
     pub steam_id: String,
 }
 
+impl Player {
+    /// Bots all have the SteamID `BOT` so they are told apart by name
+    fn key(&self) -> (&str, &str) {
+        if self.steam_id == "BOT" {
+            (&self.steam_id, &self.name)
+        } else {
+            (&self.steam_id, "")
+        }
+    }
+}
+
+/// Compare two player lists from `status` by SteamID, returning [`Event::PlayerLeft`],
+/// [`Event::PlayerJoined`] and [`Event::PlayerRenamed`] events.
+pub fn diff_players(old: &[Player], new: &[Player]) -> Vec<Event> {
+    let old_players: HashMap<_, _> = old.iter().map(|player| (player.key(), player)).collect();
+    let new_keys: HashSet<_> = new.iter().map(Player::key).collect();
+
+    let mut events: Vec<Event> = old
+        .iter()
+        .filter(|player| !new_keys.contains(&player.key()))
+        .map(|player| Event::PlayerLeft(player.clone()))
+        .collect();
+    for player in new {
+        match old_players.get(&player.key()) {
+            None => events.push(Event::PlayerJoined(player.clone())),
+            Some(old_player) if old_player.name != player.name => {
+                events.push(Event::PlayerRenamed {
+                    steam_id: player.steam_id.clone(),
+                    old_name: old_player.name.clone(),
+                    new_name: player.name.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    events
+}
+
 impl StatusData {
     pub async fn parse<T: AsyncRead + AsyncReadExt + Send>(
         hostname: String,
@@ -76,7 +118,7 @@ impl StatusData {
         loop {
             let line = reader.read_line().await?;
             let line = line.trim();
-            eprintln!("Parsing {:?}", line);
+            log::trace!("Parsing {:?}", line);
 
             if line == "#end" {
                 break;
@@ -0,0 +1,27 @@
+/// POST a JSON `body` to `url` in the background.
+#[cfg(feature = "webhook")]
+pub fn post(url: String, body: String) {
+    tokio::spawn(async move {
+        let result = reqwest::Client::new()
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await;
+        match result {
+            Ok(response) if !response.status().is_success() => {
+                log::warn!("Webhook {} returned {}", url, response.status())
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Error calling webhook {} {}", url, e),
+        }
+    });
+}
+
+#[cfg(not(feature = "webhook"))]
+pub fn post(url: String, _body: String) {
+    log::warn!(
+        "Skipping webhook {}, it needs the webhook cargo feature",
+        url
+    );
+}
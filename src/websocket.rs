@@ -0,0 +1,185 @@
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio_tungstenite::tungstenite::handshake::server::{
+    Callback, ErrorResponse, Request, Response,
+};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::{Error, Message};
+
+use csgo_netcon::listener::Context;
+use csgo_netcon::types::{Event, EventDiscriminants, GenericResult, State};
+use csgo_netcon::{CommandWriter, Listener};
+
+use crate::output::{event_json, state_json};
+
+const CHANNEL_SIZE: usize = 256;
+
+/// A command sent by a client, e.g. `{"command": "say gg"}`
+#[derive(Deserialize)]
+struct CommandMessage {
+    command: String,
+}
+
+/// Pushes state snapshots and events to every connected WebSocket client, using the same JSON
+/// format as `--json`.
+///
+/// Clients receive the current state when they connect and can send commands to the console
+/// as `{"command": "..."}`, each command is answered with `{"command": "...", "result": "ok"}`
+/// or `{"command": "...", "error": "..."}`.
+///
+/// Any web page can open a WebSocket to localhost, so connections from a browser are refused
+/// unless their `Origin` is in `allowed_origins`. Clients that send no `Origin`, like scripts,
+/// are always accepted.
+pub struct WebSocketServer {
+    messages: broadcast::Sender<String>,
+    state: watch::Sender<String>,
+}
+
+impl WebSocketServer {
+    pub async fn start(
+        address: &str,
+        writer: Option<CommandWriter>,
+        allowed_origins: Vec<String>,
+    ) -> GenericResult<Self> {
+        let listener = TcpListener::bind(address).await?;
+        log::info!("WebSocket server listening on {}", listener.local_addr()?);
+
+        let (messages, _) = broadcast::channel(CHANNEL_SIZE);
+        let (state, _) = watch::channel(state_json(&State::default()));
+
+        {
+            let messages = messages.clone();
+            let state = state.subscribe();
+            let allowed_origins = std::sync::Arc::new(allowed_origins);
+            tokio::spawn(async move {
+                loop {
+                    let (stream, peer) = match listener.accept().await {
+                        Ok(conn) => conn,
+                        Err(e) => {
+                            log::error!("WebSocket accept error {:?}", e);
+                            continue;
+                        }
+                    };
+                    log::debug!("WebSocket client connected {}", peer);
+                    let messages = messages.subscribe();
+                    let state = state.clone();
+                    let writer = writer.clone();
+                    let allowed_origins = allowed_origins.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) =
+                            handle_client(stream, messages, state, writer, &allowed_origins).await
+                        {
+                            log::debug!("WebSocket client {} error {:?}", peer, e);
+                        }
+                    });
+                }
+            });
+        }
+
+        Ok(Self { messages, state })
+    }
+
+    pub fn send_event(&self, event: &Event) {
+        // No receivers is not an error, there may be no clients
+        let _ = self.messages.send(event_json(event));
+    }
+
+    pub fn send_state(&self, state: &State) {
+        let json = state_json(state);
+        self.state.send_replace(json.clone());
+        let _ = self.messages.send(json);
+    }
+}
+
+#[async_trait]
+impl Listener for WebSocketServer {
+    async fn on_event(&mut self, _ctx: &Context, event: &Event) {
+        if !event.is_variant(EventDiscriminants::Tick) {
+            self.send_event(event);
+        }
+    }
+
+    async fn on_state_change(&mut self, _ctx: &Context, state: &State) {
+        self.send_state(state);
+    }
+}
+
+async fn handle_client(
+    stream: TcpStream,
+    mut messages: broadcast::Receiver<String>,
+    state: watch::Receiver<String>,
+    writer: Option<CommandWriter>,
+    allowed_origins: &[String],
+) -> Result<(), Error> {
+    let (mut sink, mut source) =
+        tokio_tungstenite::accept_hdr_async(stream, OriginCheck(allowed_origins))
+            .await?
+            .split();
+
+    let snapshot = state.borrow().clone();
+    sink.send(Message::Text(snapshot)).await?;
+
+    loop {
+        tokio::select! {
+            message = messages.recv() => match message {
+                Ok(message) => sink.send(Message::Text(message)).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("WebSocket client skipped {} messages", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            message = source.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = handle_command(&writer, &text).await;
+                    sink.send(Message::Text(reply)).await?;
+                }
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e),
+            },
+        }
+    }
+}
+
+/// Refuses handshakes from browser pages that are not allowed to send commands.
+struct OriginCheck<'a>(&'a [String]);
+
+impl Callback for OriginCheck<'_> {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        let origin = match request.headers().get("Origin") {
+            Some(origin) => origin.to_str().unwrap_or_default(),
+            None => return Ok(response),
+        };
+        if self.0.iter().any(|allowed| allowed == origin) {
+            return Ok(response);
+        }
+        log::warn!("Refused WebSocket connection from origin {:?}", origin);
+        let mut response = ErrorResponse::new(Some(String::from("Origin not allowed")));
+        *response.status_mut() = StatusCode::FORBIDDEN;
+        Err(response)
+    }
+}
+
+async fn handle_command(writer: &Option<CommandWriter>, text: &str) -> String {
+    let message: CommandMessage = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(e) => return serde_json::json!({ "error": e.to_string() }).to_string(),
+    };
+
+    let result = match writer {
+        Some(writer) => writer
+            .send(message.command.as_str())
+            .await
+            .map_err(|e| e.to_string()),
+        None => Err(String::from("Commands cannot be sent while replaying")),
+    };
+
+    match result {
+        Ok(()) => serde_json::json!({ "command": message.command, "result": "ok" }),
+        Err(e) => serde_json::json!({ "command": message.command, "error": e }),
+    }
+    .to_string()
+}
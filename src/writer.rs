@@ -0,0 +1,182 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, oneshot};
+use tokio::time::timeout;
+
+use crate::constants::{MAX_COMMAND_LENGTH, STATE_QUERIES};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandError {
+    /// The command could not be written to the console
+    Write(String),
+    /// The command writer has stopped
+    Closed,
+    /// The console did not respond in time
+    Timeout,
+    /// Lines of console output were missed, so the output would be incomplete
+    Lagged(u64),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Write(e) => write!(f, "Failed to write command: {}", e),
+            CommandError::Closed => write!(f, "Command writer closed"),
+            CommandError::Timeout => write!(f, "Timed out waiting for console output"),
+            CommandError::Lagged(skipped) => {
+                write!(f, "Missed {} lines of console output", skipped)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+struct PendingCommand {
+    command: String,
+    reply: Option<oneshot::Sender<Result<(), CommandError>>>,
+}
+
+/// A handle to the task that writes commands to the console.
+///
+/// CS:GO crashes if 2 or more writes are made to a socket in between each read, so all commands
+/// are funneled through a single task. Each write opens a new connection, and after every write
+/// the task waits for a line of console output (or the settle time to pass) before writing again.
+/// Commands that queue up in the meantime are joined with `;` into a single write, state queries
+/// like `status` that are already part of the write are not repeated.
+#[derive(Clone)]
+pub struct CommandWriter {
+    tx: async_channel::Sender<PendingCommand>,
+}
+
+impl CommandWriter {
+    /// Spawn a writer for the console at `addr`, `lines` should receive every line of console output.
+    pub fn spawn(addr: SocketAddr, lines: broadcast::Receiver<String>, settle: Duration) -> Self {
+        let (tx, rx) = async_channel::unbounded();
+        tokio::spawn(write_commands(addr, rx, lines, settle));
+        Self { tx }
+    }
+
+    /// Send a command and wait until it has been written to the console.
+    pub async fn send(&self, command: impl Into<String>) -> Result<(), CommandError> {
+        let (reply, result) = oneshot::channel();
+        self.tx
+            .send(PendingCommand {
+                command: command.into(),
+                reply: Some(reply),
+            })
+            .await
+            .map_err(|_| CommandError::Closed)?;
+        result.await.map_err(|_| CommandError::Closed)?
+    }
+
+    /// Queue a command without waiting for it to be written, write failures are logged.
+    pub fn queue(&self, command: impl Into<String>) -> Result<(), CommandError> {
+        self.tx
+            .try_send(PendingCommand {
+                command: command.into(),
+                reply: None,
+            })
+            .map_err(|_| CommandError::Closed)
+    }
+}
+
+async fn write_commands(
+    addr: SocketAddr,
+    rx: async_channel::Receiver<PendingCommand>,
+    mut lines: broadcast::Receiver<String>,
+    settle: Duration,
+) {
+    let mut carry = None;
+    loop {
+        let first = match carry.take() {
+            Some(pending) => pending,
+            None => match rx.recv().await {
+                Ok(pending) => pending,
+                Err(_) => return,
+            },
+        };
+
+        let mut batch = vec![first];
+        let mut data = normalize(&batch[0].command);
+        while let Ok(pending) = rx.try_recv() {
+            let command = normalize(&pending.command);
+            if command.is_empty() || already_queried(&data, &command) {
+                // Already part of this write
+                batch.push(pending);
+            } else if data.len() + command.len() + 1 > MAX_COMMAND_LENGTH {
+                carry = Some(pending);
+                break;
+            } else {
+                if !data.is_empty() {
+                    data.push(';');
+                }
+                data.push_str(&command);
+                batch.push(pending);
+            }
+        }
+
+        if data.is_empty() {
+            for pending in batch {
+                reply(pending, Ok(()));
+            }
+            continue;
+        }
+
+        // Forget output from before this write, the receiver may have lagged while idle
+        while let Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) = lines.try_recv() {}
+
+        data.push('\n');
+        let result = write(&addr, data.as_bytes())
+            .await
+            .map_err(|e| CommandError::Write(e.to_string()));
+        if let Err(e) = &result {
+            log::error!("Error writing command {:?} {}", data, e);
+        }
+        for pending in batch {
+            reply(pending, result.clone());
+        }
+
+        // Wait for console output before allowing another write
+        let _ = timeout(settle, async {
+            while let Err(broadcast::error::RecvError::Lagged(_)) = lines.recv().await {}
+        })
+        .await;
+    }
+}
+
+/// Remove the characters that would end a quoted argument or start another command, so text
+/// from other players can be put in a command.
+pub fn sanitize_argument(text: &str) -> String {
+    text.chars()
+        .filter(|c| !matches!(c, ';' | '"' | '\n' | '\r'))
+        .collect()
+}
+
+/// Whether `command` only queries state that `data` already queries.
+fn already_queried(data: &str, command: &str) -> bool {
+    command
+        .split(';')
+        .all(|part| STATE_QUERIES.contains(&part) && data.split(';').any(|c| c == part))
+}
+
+fn normalize(command: &str) -> String {
+    command.trim().trim_end_matches(';').to_string()
+}
+
+fn reply(pending: PendingCommand, result: Result<(), CommandError>) {
+    if let Some(reply) = pending.reply {
+        // The caller may have stopped waiting
+        let _ = reply.send(result);
+    }
+}
+
+async fn write(addr: &SocketAddr, data: &[u8]) -> tokio::io::Result<()> {
+    let mut cmd_conn = TcpStream::connect(addr).await?;
+    cmd_conn.write_all(data).await?;
+    Ok(())
+}
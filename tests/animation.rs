@@ -0,0 +1,74 @@
+use std::time::{Duration, Instant};
+
+use csgo_netcon::animation::{AnimationConfig, CvarAnimator, Easing};
+use csgo_netcon::types::{Damage, DamageDirection, DamageReport, Event, State};
+
+const ANIMATIONS: &str = r#"
+[[animations]]
+cvar = "cl_hud_color"
+keyframes = [{ time = 0, value = 0 }, { time = 1000, value = 10 }]
+repeat = true
+
+[[animations]]
+cvar = "cl_hud_color"
+on = "damage_taken"
+easing = "step"
+keyframes = [{ time = 0, value = 3 }, { time = 500, value = 3 }]
+"#;
+
+#[derive(serde::Deserialize)]
+struct Animations {
+    animations: Vec<AnimationConfig>,
+}
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn eases_between_keyframes() {
+    assert_eq!(Easing::Linear.apply(0.25), 0.25);
+    assert_eq!(Easing::EaseIn.apply(0.5), 0.25);
+    assert_eq!(Easing::EaseOut.apply(0.5), 0.75);
+    assert_eq!(Easing::Step.apply(0.9), 0.0);
+
+    let config: Animations = toml::from_str(ANIMATIONS).unwrap();
+    let looping = &config.animations[0];
+    assert_eq!(looping.value_at(ms(0)), 0.0);
+    assert_eq!(looping.value_at(ms(250)), 2.5);
+    assert_eq!(looping.value_at(ms(2000)), 10.0);
+}
+
+#[test]
+fn reactions_interrupt_loops() {
+    let config: Animations = toml::from_str(ANIMATIONS).unwrap();
+    let mut animator = CvarAnimator::new(&config.animations);
+    let state = State::default();
+    let start = Instant::now();
+
+    assert_eq!(animator.tick(start), vec!["cl_hud_color 0"]);
+    assert_eq!(animator.tick(start + ms(500)), vec!["cl_hud_color 5"]);
+    // Unchanged values are not sent again
+    assert!(animator.tick(start + ms(1500)).is_empty());
+    assert_eq!(animator.tick(start + ms(1600)), vec!["cl_hud_color 6"]);
+
+    let mut report = DamageReport::new("Player");
+    report.taken.push(Damage {
+        direction: DamageDirection::Taken,
+        target: String::from("Enemy"),
+        amount: 20,
+        hits: 1,
+    });
+    // Single damage lines are part of a report and do not start the reaction
+    animator.event(
+        &Event::Damage(report.taken[0].clone()),
+        &state,
+        start + ms(1650),
+    );
+    assert!(animator.tick(start + ms(1650)).is_empty());
+    animator.event(&Event::DamageReport(report), &state, start + ms(1700));
+    assert_eq!(animator.tick(start + ms(1800)), vec!["cl_hud_color 3"]);
+    assert!(animator.tick(start + ms(2300)).is_empty());
+    // The loop takes over again once the reaction has finished
+    assert_eq!(animator.tick(start + ms(2400)), vec!["cl_hud_color 4"]);
+}
@@ -0,0 +1,70 @@
+use csgo_netcon::types::{parse_chat, ChatScope, Event, Team};
+
+fn chat(
+    sender: &str,
+    team: Option<Team>,
+    dead: bool,
+    location: Option<&str>,
+    text: &str,
+    scope: ChatScope,
+) -> Event {
+    Event::Chat {
+        sender: sender.to_string(),
+        team,
+        dead,
+        location: location.map(|l| l.to_string()),
+        text: text.to_string(),
+        scope,
+    }
+}
+
+#[test]
+fn parses_all_chat() {
+    assert_eq!(
+        parse_chat("Player One : gl hf"),
+        Ok(chat(
+            "Player One",
+            None,
+            false,
+            None,
+            "gl hf",
+            ChatScope::All
+        ))
+    );
+    assert_eq!(
+        parse_chat("*DEAD* \u{200E}Player One\u{200E} :  nt"),
+        Ok(chat("Player One", None, true, None, "nt", ChatScope::All))
+    );
+}
+
+#[test]
+fn parses_team_chat() {
+    assert_eq!(
+        parse_chat("(Counter-Terrorist) Player One @ Bombsite A : rotate B"),
+        Ok(chat(
+            "Player One",
+            Some(Team::CounterTerrorist),
+            false,
+            Some("Bombsite A"),
+            "rotate B",
+            ChatScope::Team
+        ))
+    );
+    assert_eq!(
+        parse_chat("*DEAD*(Terrorist) Player Two : one left mid"),
+        Ok(chat(
+            "Player Two",
+            Some(Team::Terrorist),
+            true,
+            None,
+            "one left mid",
+            ChatScope::Team
+        ))
+    );
+}
+
+#[test]
+fn ignores_other_output() {
+    assert!(parse_chat("cl_hud_color : 0 : , \"a\", \"cl\" : 0 - 10").is_err());
+    assert!(parse_chat("Unknown command \"foo\"").is_err());
+}
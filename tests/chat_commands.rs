@@ -0,0 +1,74 @@
+mod common;
+
+use std::time::Duration;
+
+use csgo_netcon::chat_commands::{ChatCommand, ChatCommandConfig, ChatCommandRouter};
+use csgo_netcon::listener::{Context, Listeners};
+use csgo_netcon::NetconClient;
+
+use common::MockServer;
+
+/// Forward every event from the client to the listeners.
+async fn spawn_listeners(server: &MockServer, client: &NetconClient, router: ChatCommandRouter) {
+    server.wait_for_connection().await;
+    let mut listeners = Listeners::new(Context::new(Some(client.writer())));
+    listeners.register(Box::new(router));
+    let events = client.events();
+    tokio::spawn(async move {
+        while let Ok(event) = events.recv().await {
+            listeners.event(&event);
+        }
+    });
+}
+
+#[tokio::test]
+async fn replies_to_commands_with_cooldown() {
+    let server = MockServer::start().await;
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+    spawn_listeners(
+        &server,
+        &client,
+        ChatCommandRouter::from_config(&ChatCommandConfig::default()),
+    )
+    .await;
+
+    server.output(&["Player One : !adr"]);
+    assert!(server.wait_for_command("say \"0 damage\"").await);
+
+    server.output(&["(Terrorist) Player One @ Mid : !adr"]);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(
+        server
+            .commands()
+            .iter()
+            .filter(|command| command.starts_with("say"))
+            .count(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn ignores_senders_not_in_allowlist() {
+    let server = MockServer::start().await;
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+    let router = ChatCommandRouter::new("!").command(
+        "hello",
+        ChatCommand::new(Box::new(|invocation, _| {
+            Some(format!("!hello {}", invocation.sender))
+        }))
+        .allowlist(vec![String::from("Player Two")]),
+    );
+    spawn_listeners(&server, &client, router).await;
+
+    server.output(&["Player One : !hello"]);
+    server.output(&["*DEAD*(Counter-Terrorist) Player Two : !hello"]);
+    assert!(
+        server
+            .wait_for_command("say_team \"hello Player Two\"")
+            .await
+    );
+    assert!(!server
+        .commands()
+        .iter()
+        .any(|command| command.contains("Player One")));
+}
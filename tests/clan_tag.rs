@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+use csgo_netcon::clan_tag::{marquee, render_frame, ClanTagAnimator, ClanTagConfig};
+use csgo_netcon::types::{Event, State};
+
+#[test]
+fn scrolls_marquee() {
+    assert_eq!(marquee("ab", 3), vec!["   ", "  a", " ab", "ab ", "b  "]);
+}
+
+#[test]
+fn renders_frames_from_state() {
+    let mut state = State::default();
+    state.update(&Event::MapChange(String::from("de_mirage")));
+    state.update(&Event::EnterBuyPeriod);
+    assert_eq!(render_frame("R{round} {map}", &state), "R1 de_mirage");
+    assert_eq!(
+        render_frame("a;very\"long frame text", &state),
+        "averylong frame"
+    );
+}
+
+#[test]
+fn cycles_frames_on_interval() {
+    let mut animator = ClanTagAnimator::new(&ClanTagConfig {
+        frames: vec![String::from("one"), String::from("two")],
+        interval: 1000,
+        ..Default::default()
+    });
+    let state = State::default();
+    let start = Instant::now();
+
+    assert_eq!(
+        animator.tick(&state, start),
+        Some(String::from("clan \"one\""))
+    );
+    assert_eq!(
+        animator.tick(&state, start + Duration::from_millis(500)),
+        None
+    );
+    assert_eq!(
+        animator.tick(&state, start + Duration::from_millis(1000)),
+        Some(String::from("clan \"two\""))
+    );
+    assert_eq!(
+        animator.tick(&state, start + Duration::from_millis(2000)),
+        Some(String::from("clan \"one\""))
+    );
+
+    // Nothing is sent without frames
+    let mut empty = ClanTagAnimator::new(&ClanTagConfig::default());
+    assert!(empty.is_empty());
+    assert_eq!(empty.tick(&state, start), None);
+}
+
+#[test]
+fn fills_in_marquee_before_scrolling() {
+    let mut animator = ClanTagAnimator::new(&ClanTagConfig {
+        marquee: Some(String::from("on {map}")),
+        width: 5,
+        interval: 1000,
+        ..Default::default()
+    });
+    let mut state = State::default();
+    state.update(&Event::MapChange(String::from("de_nuke")));
+    let start = Instant::now();
+
+    let frames: Vec<_> = (0..15)
+        .filter_map(|i| animator.tick(&state, start + Duration::from_secs(i)))
+        .collect();
+    let expected: Vec<_> = marquee("on de_nuke", 5)
+        .iter()
+        .map(|frame| format!("clan \"{}\"", frame))
+        .collect();
+    assert_eq!(frames, expected);
+}
@@ -0,0 +1,247 @@
+mod common;
+
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tokio::time::sleep;
+
+use csgo_netcon::types::{Event, Status, UIState};
+use csgo_netcon::{ClientOptions, CommandError, NetconClient};
+
+use common::{wait_for_event, MockServer};
+
+#[tokio::test]
+async fn resynchronises_state_on_connect() {
+    let server = MockServer::start().await;
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+
+    let events = client.events();
+    assert_eq!(
+        wait_for_event(&events, |_| true).await,
+        Some(Event::Connected)
+    );
+    for command in ["status", "game_type", "game_mode"] {
+        assert!(server.wait_for_command(command).await, "{}", command);
+    }
+}
+
+#[tokio::test]
+async fn parses_console_output() {
+    let server = MockServer::start().await;
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+    let events = client.events();
+    wait_for_event(&events, |event| *event == Event::Connected).await;
+
+    server.output(&[
+        "ChangeGameUIState: CSGO_GAME_UI_STATE_LOADINGSCREEN -> CSGO_GAME_UI_STATE_INGAME",
+        "Map: de_mirage",
+        "\"game_type\" = \"0\" ( def. \"0\" ) game replicated",
+    ]);
+
+    assert_eq!(
+        wait_for_event(&events, |event| event
+            .is_variant(csgo_netcon::types::EventDiscriminants::ChangeUIState))
+        .await,
+        Some(Event::ChangeUIState(
+            UIState::LoadingScreen,
+            UIState::InGame
+        ))
+    );
+    assert_eq!(
+        wait_for_event(&events, |_| true).await,
+        Some(Event::MapChange(String::from("de_mirage")))
+    );
+    assert_eq!(
+        wait_for_event(&events, |_| true).await,
+        Some(Event::ConVar(String::from("game_type"), String::from("0")))
+    );
+}
+
+#[tokio::test]
+async fn query_returns_command_output() {
+    let server = MockServer::start().await;
+    server.reply(
+        "find hud_color",
+        &["\"cl_hud_color\" = \"0\"", "1 convar found"],
+    );
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+
+    let output = client.query("find hud_color").await.unwrap();
+    assert_eq!(output, vec!["\"cl_hud_color\" = \"0\"", "1 convar found"]);
+}
+
+#[tokio::test]
+async fn query_parses_status() {
+    let server = MockServer::start().await;
+    server.reply(
+        "status",
+        &[
+            "hostname: Valve CS:GO EU West Server (srcds123.45.67)",
+            "version : 1.38.2.9/13829 1503/8441 secure  [G:1:1234567] ",
+            "udp/ip  : 0.0.0.0:27015  (public ip: 1.2.3.4)",
+            "os      :  Linux",
+            "type    :  official dedicated",
+            "map     : de_inferno",
+            "players : 9 humans, 1 bots (10/0 max) (not hibernating)",
+            "# userid name uniqueid connected ping loss state rate",
+            "#  2 1 \"Player One\" STEAM_1:0:1234 10:00 30 0 active 786432",
+            "#end",
+        ],
+    );
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+
+    let status = client.status().await.unwrap();
+    assert_eq!(status.map, "de_inferno");
+    assert_eq!(status.players.humans, 9);
+    assert_eq!(status.player_list.len(), 1);
+    assert_eq!(status.player_list[0].name, "Player One");
+    assert_eq!(status.player_list[0].steam_id, "STEAM_1:0:1234");
+
+    let events = client.events();
+    assert!(matches!(
+        wait_for_event(&events, |event| matches!(event, Event::Status(_))).await,
+        Some(Event::Status(Status::Connected(_)))
+    ));
+}
+
+#[tokio::test]
+async fn serialises_concurrent_commands() {
+    let server = MockServer::start().await;
+    server.crash_on_double_write();
+    // Like the game, answer the resync so the next write is allowed
+    server.reply("status", &["Not connected to server"]);
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+
+    let sends = (0..20).map(|i| {
+        let writer = client.writer();
+        tokio::spawn(async move { writer.send(format!("echo {}", i)).await })
+    });
+    for send in sends {
+        send.await.unwrap().unwrap();
+    }
+
+    for i in 0..20 {
+        assert!(server.wait_for_command(&format!("echo {}", i)).await);
+    }
+    assert!(!server.crashed());
+}
+
+#[tokio::test]
+async fn waits_for_output_after_idle_output() {
+    let server = MockServer::start().await;
+    server.crash_on_double_write();
+    server.reply("status", &["Not connected to server"]);
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+    server.wait_for_connection().await;
+    assert!(server.wait_for_command("status").await);
+
+    // More output than the writer buffers while it has nothing to write
+    for i in 0..2000 {
+        server.output(&[&format!("spam {}", i)]);
+        if i % 200 == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+    sleep(Duration::from_millis(300)).await;
+
+    let writer = client.writer();
+    writer.send("echo first").await.unwrap();
+    writer.send("echo second").await.unwrap();
+    assert!(server.wait_for_command("echo second").await);
+    assert!(!server.crashed());
+}
+
+#[tokio::test]
+async fn skips_resync_when_disabled() {
+    let server = MockServer::start().await;
+    let options = ClientOptions {
+        resync: false,
+        ..ClientOptions::default()
+    };
+    let client = NetconClient::connect_with(server.addr(), options)
+        .await
+        .unwrap();
+
+    client.send_command("echo hello").await.unwrap();
+    assert!(server.wait_for_command("echo hello").await);
+    assert_eq!(server.commands(), vec![String::from("echo hello")]);
+}
+
+#[tokio::test]
+async fn writes_repeated_commands() {
+    let server = MockServer::start().await;
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+    assert!(server.wait_for_command("status").await);
+
+    let writer = client.writer();
+    writer.queue("status").unwrap();
+    writer.queue("say gg").unwrap();
+    writer.queue("status").unwrap();
+    writer.send("say gg").await.unwrap();
+    writer.send("echo done").await.unwrap();
+    assert!(server.wait_for_command("echo done").await);
+
+    let commands = server.commands();
+    assert_eq!(commands.iter().filter(|c| *c == "say gg").count(), 2);
+    // Only the resync and one of the queued queries
+    assert_eq!(commands.iter().filter(|c| *c == "status").count(), 2);
+}
+
+#[tokio::test]
+async fn query_returns_long_output() {
+    let server = MockServer::start().await;
+    let lines: Vec<String> = (0..3000)
+        .map(|i| format!("cvar_{} : 0 : , \"cl\"", i))
+        .collect();
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    server.reply("cvarlist", &lines);
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+
+    let output = client.query("cvarlist").await.unwrap();
+    assert_eq!(output, lines);
+}
+
+#[tokio::test]
+async fn query_fails_when_output_is_missed() {
+    let server = MockServer::start().await;
+    let lines: Vec<String> = (0..1000).map(|i| format!("line {}", i)).collect();
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    server.reply("cvarlist", &lines);
+    let options = ClientOptions {
+        lines: Some(broadcast::channel(16).0),
+        ..ClientOptions::default()
+    };
+    let client = NetconClient::connect_with(server.addr(), options)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        client.query("cvarlist").await,
+        Err(CommandError::Lagged(_))
+    ));
+}
+
+#[tokio::test]
+async fn reconnects_after_disconnect() {
+    let server = MockServer::start().await;
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+    let events = client.events();
+    wait_for_event(&events, |event| *event == Event::Connected).await;
+
+    server.kick();
+
+    assert_eq!(
+        wait_for_event(&events, |event| *event == Event::Disconnected).await,
+        Some(Event::Disconnected)
+    );
+    assert_eq!(
+        wait_for_event(&events, |event| *event == Event::Connected).await,
+        Some(Event::Connected)
+    );
+
+    server.output(&["Map: de_nuke"]);
+    assert_eq!(
+        wait_for_event(&events, |event| matches!(event, Event::MapChange(_))).await,
+        Some(Event::MapChange(String::from("de_nuke")))
+    );
+}
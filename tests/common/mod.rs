@@ -0,0 +1,235 @@
+//! An in-process fake of the CS:GO netcon port.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio::time::{sleep, timeout, Instant};
+
+/// Lines of output buffered for each connection, enough for long replies like `cvarlist`
+const OUTPUT_CHANNEL_SIZE: usize = 16384;
+
+/// How long the server takes to answer a command when it crashes on double writes
+const REPLY_DELAY: Duration = Duration::from_millis(50);
+
+#[derive(Default)]
+struct Inner {
+    commands: Vec<String>,
+    replies: HashMap<String, Vec<String>>,
+    crash_on_double_write: bool,
+    /// Whether any connection has written since the last output, the writer opens a new
+    /// connection for every write so this is shared
+    written: bool,
+    crashed: bool,
+    connections: usize,
+}
+
+/// A netcon server that records every command it receives and writes scripted console output
+/// to every open connection.
+pub struct MockServer {
+    addr: SocketAddr,
+    inner: Arc<Mutex<Inner>>,
+    output: broadcast::Sender<String>,
+    kick: watch::Sender<u64>,
+}
+
+impl MockServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let inner = Arc::new(Mutex::new(Inner::default()));
+        let (output, _) = broadcast::channel(OUTPUT_CHANNEL_SIZE);
+        let (kick, _) = watch::channel(0);
+
+        {
+            let inner = inner.clone();
+            let output = output.clone();
+            let kick = kick.subscribe();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    // Subscribe before counting the connection so no output is missed
+                    let lines = output.subscribe();
+                    {
+                        let mut inner = inner.lock().unwrap();
+                        if inner.crashed {
+                            return;
+                        }
+                        inner.connections += 1;
+                    }
+                    tokio::spawn(handle_connection(
+                        stream,
+                        inner.clone(),
+                        output.clone(),
+                        lines,
+                        kick.clone(),
+                    ));
+                }
+            });
+        }
+
+        Self {
+            addr,
+            inner,
+            output,
+            kick,
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Print `lines` whenever `command` is received.
+    pub fn reply(&self, command: &str, lines: &[&str]) {
+        self.inner.lock().unwrap().replies.insert(
+            command.to_string(),
+            lines.iter().map(|line| line.to_string()).collect(),
+        );
+    }
+
+    /// Print `lines` to every open connection.
+    pub fn output(&self, lines: &[&str]) {
+        for line in lines {
+            print_line(&self.inner, &self.output, line.to_string());
+        }
+    }
+
+    /// Every command received so far, compound commands are split on `;`.
+    pub fn commands(&self) -> Vec<String> {
+        self.inner.lock().unwrap().commands.clone()
+    }
+
+    /// Wait until `command` has been received.
+    pub async fn wait_for_command(&self, command: &str) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if self.commands().iter().any(|c| c == command) {
+                return true;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        false
+    }
+
+    /// Wait until a client has connected, output is only sent to open connections.
+    pub async fn wait_for_connection(&self) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while self.inner.lock().unwrap().connections == 0 && Instant::now() < deadline {
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Behave like the game and crash if it is written to twice without any output in between.
+    pub fn crash_on_double_write(&self) {
+        self.inner.lock().unwrap().crash_on_double_write = true;
+    }
+
+    pub fn crashed(&self) -> bool {
+        self.inner.lock().unwrap().crashed
+    }
+
+    /// Close every open connection, as if the game restarted.
+    pub fn kick(&self) {
+        self.kick.send_modify(|generation| *generation += 1);
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    inner: Arc<Mutex<Inner>>,
+    output: broadcast::Sender<String>,
+    mut lines: broadcast::Receiver<String>,
+    mut kick: watch::Receiver<u64>,
+) {
+    let (rd, mut wr) = stream.into_split();
+    let mut rd = BufReader::new(rd).lines();
+    kick.borrow_and_update();
+
+    loop {
+        tokio::select! {
+            line = lines.recv() => {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                if wr.write_all(format!("{}\n", line).as_bytes()).await.is_err() {
+                    return;
+                }
+            }
+            line = rd.next_line() => {
+                let line = match line {
+                    Ok(Some(line)) => line,
+                    _ => return,
+                };
+                let mut output_lines = Vec::new();
+                let slow;
+                {
+                    let mut inner = inner.lock().unwrap();
+                    slow = inner.crash_on_double_write;
+                    if inner.written && inner.crash_on_double_write {
+                        inner.crashed = true;
+                    }
+                    if inner.crashed {
+                        return;
+                    }
+                    inner.written = true;
+                    for command in line.split(';').map(str::trim).filter(|c| !c.is_empty()) {
+                        inner.commands.push(command.to_string());
+                        if let Some(text) = command.strip_prefix("echo ") {
+                            output_lines.push(text.to_string());
+                        }
+                        if let Some(reply) = inner.replies.get(command) {
+                            output_lines.extend(reply.iter().cloned());
+                        }
+                    }
+                }
+                // The game takes a moment to answer, a writer that does not wait for it crashes
+                let inner = inner.clone();
+                let output = output.clone();
+                tokio::spawn(async move {
+                    if slow {
+                        sleep(REPLY_DELAY).await;
+                    }
+                    for line in output_lines {
+                        print_line(&inner, &output, line);
+                    }
+                });
+            }
+            _ = kick.changed() => return,
+        }
+    }
+}
+
+/// Print `line` to every open connection, which allows the next write.
+fn print_line(inner: &Mutex<Inner>, output: &broadcast::Sender<String>, line: String) {
+    let mut inner = inner.lock().unwrap();
+    inner.written = false;
+    let _ = output.send(line);
+}
+
+/// Wait for an event matching `predicate`, skipping any others.
+pub async fn wait_for_event<F>(
+    events: &async_channel::Receiver<csgo_netcon::types::Event>,
+    mut predicate: F,
+) -> Option<csgo_netcon::types::Event>
+where
+    F: FnMut(&csgo_netcon::types::Event) -> bool,
+{
+    timeout(Duration::from_secs(5), async {
+        while let Ok(event) = events.recv().await {
+            if predicate(&event) {
+                return Some(event);
+            }
+        }
+        None
+    })
+    .await
+    .ok()
+    .flatten()
+}
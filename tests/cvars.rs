@@ -0,0 +1,86 @@
+mod common;
+
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use csgo_netcon::cvars::Cvars;
+use csgo_netcon::types::Event;
+use csgo_netcon::{ClientOptions, NetconClient};
+
+use common::MockServer;
+
+#[test]
+fn learns_cvarlist_and_completes() {
+    let cvars = Cvars::default();
+    let learned = cvars.learn_cvarlist(&[
+        "cvar list",
+        "--------------",
+        "cl_hud_color                             : 0        : , \"a\", \"cl\"         : 0 = default, 1 = white",
+        "cl_hud_radar_scale                       : 1        : , \"a\", \"cl\"         : ",
+        "clear                                    : cmd      :                  : Clear all console output.",
+        "--------------",
+        "  3 total convars/concommands",
+    ]);
+    assert_eq!(learned, 3);
+
+    cvars.learn(&Event::ConVar(
+        String::from("cl_hud_background_alpha"),
+        String::from("0.5"),
+    ));
+
+    assert_eq!(
+        cvars.complete("cl_hud_"),
+        vec![
+            "cl_hud_background_alpha",
+            "cl_hud_color",
+            "cl_hud_radar_scale"
+        ]
+    );
+    assert_eq!(cvars.complete("cle"), vec!["clear"]);
+    assert!(cvars.complete("sv_").is_empty());
+}
+
+#[tokio::test]
+async fn lists_cvars_in_pieces_when_output_is_missed() {
+    let names: Vec<String> = ('a'..='z')
+        .flat_map(|c| (0..20).map(move |i| format!("{}_cvar_{}", c, i)))
+        .collect();
+    let line = |name: &String| format!("{:<40} : 0        : , \"cl\"     : ", name);
+
+    let server = MockServer::start().await;
+    let lines: Vec<String> = names.iter().map(line).collect();
+    server.reply(
+        "cvarlist",
+        &lines.iter().map(String::as_str).collect::<Vec<_>>(),
+    );
+    for c in 'a'..='z' {
+        let lines: Vec<String> = names
+            .iter()
+            .filter(|name| name.starts_with(c))
+            .map(line)
+            .collect();
+        server.reply(
+            &format!("cvarlist {}", c),
+            &lines.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+    }
+
+    // Far fewer lines fit in the channel than the full list
+    let options = ClientOptions {
+        lines: Some(broadcast::channel(64).0),
+        ..ClientOptions::default()
+    };
+    let client = NetconClient::connect_with(server.addr(), options)
+        .await
+        .unwrap();
+
+    let cvars = Cvars::default();
+    let learned = cvars
+        .load_cvarlist(&client, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(learned, names.len());
+    assert!(server.commands().contains(&String::from("cvarlist z")));
+    assert_eq!(cvars.complete("q_cvar_1").len(), 11);
+}
@@ -0,0 +1,97 @@
+use csgo_netcon::recording::replay;
+use csgo_netcon::types::{Damage, DamageDirection, DamageReport, Event, State, TargetDamage};
+
+fn damage(direction: DamageDirection, target: &str, amount: u8, hits: u8) -> Damage {
+    Damage {
+        direction,
+        target: target.to_string(),
+        amount,
+        hits,
+    }
+}
+
+async fn replay_lines(lines: &[&str]) -> Vec<Event> {
+    let path = std::env::temp_dir().join(format!(
+        "netcon-damage-{}-{}.log",
+        std::process::id(),
+        lines.len()
+    ));
+    let contents: String = lines.iter().map(|line| format!("0\t{}\n", line)).collect();
+    std::fs::write(&path, contents).unwrap();
+
+    let replayed = replay(&path, false).await.unwrap();
+    let mut events = Vec::new();
+    while let Ok(event) = replayed.recv().await {
+        events.push(event);
+    }
+    std::fs::remove_file(&path).unwrap();
+    events
+}
+
+#[tokio::test]
+async fn parses_damage_block() {
+    let events = replay_lines(&[
+        "Player: Me - Damage Given",
+        "-------------------------",
+        "Damage Given to \"Enemy One\" - 100 in 2 hits",
+        "Damage Given to \"Enemy Two\" - 27 in 1 hit",
+        "Player: Me - Damage Taken",
+        "-------------------------",
+        "Damage Taken from \"Enemy Two\" - 100 in 4 hits",
+        "EVERYONE CAN BUY!",
+    ])
+    .await;
+
+    let given_one = damage(DamageDirection::Given, "Enemy One", 100, 2);
+    let given_two = damage(DamageDirection::Given, "Enemy Two", 27, 1);
+    let taken_two = damage(DamageDirection::Taken, "Enemy Two", 100, 4);
+    assert_eq!(
+        events,
+        vec![
+            Event::Damage(given_one.clone()),
+            Event::Damage(given_two.clone()),
+            Event::Damage(taken_two.clone()),
+            Event::DamageReport(DamageReport {
+                player: String::from("Me"),
+                given: vec![given_one, given_two],
+                taken: vec![taken_two],
+            }),
+            Event::EnterBuyPeriod,
+        ]
+    );
+}
+
+#[test]
+fn marks_lethal_damage_from_kill_feed() {
+    let mut state = State::default();
+    state.update(&Event::EnterBuyPeriod);
+    state.update(&Event::Kill {
+        attacker: String::from("Me"),
+        victim: String::from("Enemy Two"),
+        weapon: String::from("deagle"),
+        headshot: true,
+        assister: None,
+    });
+    state.update(&Event::DamageReport(DamageReport {
+        player: String::from("Me"),
+        given: vec![
+            damage(DamageDirection::Given, "Enemy One", 64, 2),
+            damage(DamageDirection::Given, "Enemy Two", 85, 1),
+        ],
+        taken: vec![],
+    }));
+
+    let round = state.current_round().unwrap();
+    assert_eq!(state.player_name.as_deref(), Some("Me"));
+    assert_eq!(round.lethal_given(), 1);
+    assert_eq!(
+        round.damage["Enemy Two"],
+        TargetDamage {
+            given: 85,
+            hits_given: 1,
+            lethal_given: true,
+            ..Default::default()
+        }
+    );
+    assert!(!round.damage["Enemy One"].lethal_given);
+}
@@ -0,0 +1,68 @@
+use csgo_netcon::types::{parse_kill, Event, State};
+
+fn kill(
+    attacker: &str,
+    victim: &str,
+    weapon: &str,
+    headshot: bool,
+    assister: Option<&str>,
+) -> Event {
+    Event::Kill {
+        attacker: attacker.to_string(),
+        victim: victim.to_string(),
+        weapon: weapon.to_string(),
+        headshot,
+        assister: assister.map(|a| a.to_string()),
+    }
+}
+
+#[test]
+fn parses_console_kills() {
+    assert_eq!(
+        parse_kill("Player One killed Player Two with ak47 (headshot)"),
+        Ok(kill("Player One", "Player Two", "ak47", true, None))
+    );
+    assert_eq!(
+        parse_kill("Player One + Player Three killed Player Two with awp."),
+        Ok(kill(
+            "Player One",
+            "Player Two",
+            "awp",
+            false,
+            Some("Player Three")
+        ))
+    );
+}
+
+#[test]
+fn parses_log_kills() {
+    assert_eq!(
+        parse_kill(
+            "L 10/18/2026 - 12:00:00: \"Player One<2><STEAM_1:0:1234><CT>\" [-100 200 0] killed \"Bot<3><BOT><TERRORIST>\" [10 20 0] with \"m4a1\" (headshot)"
+        ),
+        Ok(kill("Player One", "Bot", "m4a1", true, None))
+    );
+}
+
+#[test]
+fn ignores_chat() {
+    assert!(parse_kill("Player One : he killed me with an awp").is_err());
+}
+
+#[test]
+fn tracks_player_stats() {
+    let mut state = State::default();
+    state.update(&kill("A", "B", "ak47", true, Some("C")));
+    state.update(&kill("A", "C", "ak47", false, None));
+    state.update(&kill("B", "B", "hegrenade", false, None));
+
+    let a = &state.players["A"];
+    assert_eq!((a.kills, a.deaths, a.headshots), (2, 0, 1));
+    assert_eq!(a.headshot_percentage(), 50.0);
+    assert_eq!(state.players["B"].deaths, 2);
+    assert_eq!(state.players["B"].kills, 0);
+    assert_eq!(state.players["C"].assists, 1);
+
+    state.clear_game_data(None);
+    assert!(state.players.is_empty());
+}
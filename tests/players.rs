@@ -0,0 +1,53 @@
+use csgo_netcon::types::{diff_players, Event, Player};
+
+fn player(id: &str, name: &str, steam_id: &str) -> Player {
+    Player {
+        id: id.to_string(),
+        name: name.to_string(),
+        steam_id: steam_id.to_string(),
+    }
+}
+
+#[test]
+fn reports_joins_and_leaves() {
+    let one = player("2", "Player One", "STEAM_1:0:1");
+    let two = player("3", "Player Two", "STEAM_1:0:2");
+    let three = player("4", "Player Three", "STEAM_1:0:3");
+
+    assert_eq!(
+        diff_players(&[one.clone(), two.clone()], &[one.clone(), three.clone()]),
+        vec![Event::PlayerLeft(two), Event::PlayerJoined(three)]
+    );
+    let unchanged = [one];
+    assert!(diff_players(&unchanged, &unchanged).is_empty());
+}
+
+#[test]
+fn reports_renames_by_steam_id() {
+    let old = player("2", "Player One", "STEAM_1:0:1");
+    let new = player("2", "Renamed", "STEAM_1:0:1");
+
+    assert_eq!(
+        diff_players(&[old], &[new]),
+        vec![Event::PlayerRenamed {
+            steam_id: "STEAM_1:0:1".to_string(),
+            old_name: "Player One".to_string(),
+            new_name: "Renamed".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn tells_bots_apart_by_name() {
+    let bot = player("5", "Bot Alice", "BOT");
+    let other = player("6", "Bot Bob", "BOT");
+
+    assert_eq!(
+        diff_players(std::slice::from_ref(&bot), &[bot.clone(), other.clone()]),
+        vec![Event::PlayerJoined(other.clone())]
+    );
+    assert_eq!(
+        diff_players(&[bot.clone(), other.clone()], &[other]),
+        vec![Event::PlayerLeft(bot)]
+    );
+}
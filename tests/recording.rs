@@ -0,0 +1,73 @@
+mod common;
+
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use csgo_netcon::constants::LINE_CHANNEL_SIZE;
+use csgo_netcon::recording::{record, replay};
+use csgo_netcon::types::Event;
+use csgo_netcon::{ClientOptions, NetconClient};
+
+use common::{wait_for_event, MockServer};
+
+#[tokio::test]
+async fn replays_recorded_session() {
+    let path = std::env::temp_dir().join(format!("netcon-recording-{}.log", std::process::id()));
+
+    let server = MockServer::start().await;
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+    let events = client.events();
+    wait_for_event(&events, |event| *event == Event::Connected).await;
+
+    let recorder = record(client.lines(), &path).await.unwrap();
+    server.output(&["Map: de_overpass", "EVERYONE CAN BUY!"]);
+    wait_for_event(&events, |event| *event == Event::EnterBuyPeriod).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(client);
+    recorder.abort();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.lines().all(|line| line.contains('\t')));
+
+    let replayed = replay(&path, false).await.unwrap();
+    let mut events = Vec::new();
+    while let Ok(event) = replayed.recv().await {
+        events.push(event);
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        events,
+        vec![
+            Event::MapChange(String::from("de_overpass")),
+            Event::EnterBuyPeriod
+        ]
+    );
+}
+
+#[tokio::test]
+async fn records_from_the_first_line() {
+    let path = std::env::temp_dir().join(format!("netcon-first-line-{}.log", std::process::id()));
+
+    let server = MockServer::start().await;
+    server.reply("status", &["Not connected to server"]);
+    let (lines, _) = broadcast::channel(LINE_CHANNEL_SIZE);
+    let recorder = record(lines.subscribe(), &path).await.unwrap();
+    let options = ClientOptions {
+        lines: Some(lines),
+        ..ClientOptions::default()
+    };
+    let client = NetconClient::connect_with(server.addr(), options)
+        .await
+        .unwrap();
+    assert!(server.wait_for_command("status").await);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(client);
+    recorder.abort();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    // The resync output is printed straight after connecting
+    assert!(contents.contains("\tNot connected to server"));
+}
@@ -0,0 +1,80 @@
+use csgo_netcon::types::{Damage, DamageDirection, Event, State, Status, UIState};
+
+fn damage(direction: DamageDirection, target: &str, amount: u8, hits: u8) -> Event {
+    Event::Damage(Damage {
+        direction,
+        target: target.to_string(),
+        amount,
+        hits,
+    })
+}
+
+#[test]
+fn records_damage_per_round() {
+    let mut state = State::default();
+    state.update(&damage(DamageDirection::Given, "Before", 50, 1));
+    state.update(&damage(DamageDirection::Given, "Before", 50, 1));
+    assert!(state.rounds.is_empty());
+
+    state.update(&Event::EnterBuyPeriod);
+    state.update(&damage(DamageDirection::Given, "Enemy", 100, 3));
+    state.update(&damage(DamageDirection::Given, "Enemy", 100, 3));
+    state.update(&damage(DamageDirection::Taken, "Enemy", 40, 1));
+
+    state.update(&Event::EnterBuyPeriod);
+    state.update(&damage(DamageDirection::Given, "Other", 50, 1));
+
+    assert_eq!(state.rounds.len(), 2);
+    let first = &state.rounds[0];
+    assert_eq!(first.number, 1);
+    assert!(first.end.is_some());
+    // Damage lines are round totals so a repeated block is not counted twice
+    assert_eq!(first.damage_given(), 100);
+    assert_eq!(first.hits_given(), 3);
+    assert_eq!(first.damage_taken(), 40);
+    assert_eq!(first.damage["Enemy"].hits_taken, 1);
+    // Repeated lines are counted once before the first round too
+    assert_eq!(state.total_damage_given, 200);
+    assert_eq!(state.total_damage_taken, 40);
+
+    let current = state.current_round().unwrap();
+    assert_eq!(current.number, 2);
+    assert!(current.end.is_none());
+    assert_eq!(current.damage_given(), 50);
+
+    assert_eq!(state.adr(), 75.0);
+    assert_eq!(state.average_damage_taken(), 20.0);
+}
+
+#[test]
+fn clears_rounds_on_new_game() {
+    let mut state = State::default();
+    state.update(&Event::EnterBuyPeriod);
+    state.update(&Event::MapChange("de_dust2".to_string()));
+    assert!(state.rounds.is_empty());
+    assert_eq!(state.adr(), 0.0);
+}
+
+#[test]
+fn keeps_rounds_when_reconnecting() {
+    let mut state = State::default();
+    state.update(&Event::ChangeUIState(
+        UIState::LoadingScreen,
+        UIState::InGame,
+    ));
+    state.update(&Event::MapChange("de_dust2".to_string()));
+    state.update(&Event::EnterBuyPeriod);
+    state.update(&damage(DamageDirection::Given, "Enemy", 40, 1));
+
+    state.update(&Event::Disconnected);
+    state.update(&Event::Connected);
+    assert_eq!(state.ui_state, UIState::InGame);
+    assert_eq!(state.rounds.len(), 1);
+    assert_eq!(state.adr(), 40.0);
+
+    // The game restarted so the resync finds it at the main menu
+    state.update(&Event::Disconnected);
+    state.update(&Event::Status(Status::NotConnected));
+    assert_eq!(state.ui_state, UIState::MainMenu);
+    assert!(state.rounds.is_empty());
+}
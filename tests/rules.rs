@@ -0,0 +1,119 @@
+use csgo_netcon::rules::{Action, Rules, StateAction};
+use csgo_netcon::types::{ChatScope, Damage, DamageDirection, Event, State, UIState};
+
+const RULES: &str = r#"
+[[rule]]
+name = "gg"
+debounce = 60000
+trigger = { event = "chat", text = "(?i)^gg (?P<who>\\w+)$" }
+conditions = { ui_state = "InGame" }
+actions = [{ command = "say gg {who} from {sender}" }]
+
+[[rule]]
+name = "big hit"
+trigger = { event = "damage", direction = "Given", above = 90 }
+actions = [{ webhook = { url = "http://localhost/hook", body = "{target} {amount}" } }]
+
+[[rule]]
+name = "enable on mirage"
+trigger = { event = "map_change", map = "de_mirage" }
+actions = [{ state = "enable" }, { command = "echo round {round} on {map}" }]
+"#;
+
+fn chat(sender: &str, text: &str) -> Event {
+    Event::Chat {
+        sender: sender.to_string(),
+        team: None,
+        dead: false,
+        location: None,
+        text: text.to_string(),
+        scope: ChatScope::All,
+    }
+}
+
+fn damage(amount: u8) -> Event {
+    Event::Damage(Damage {
+        direction: DamageDirection::Given,
+        target: String::from("Enemy"),
+        amount,
+        hits: 1,
+    })
+}
+
+#[test]
+fn matches_triggers_and_conditions() {
+    let mut rules = Rules::parse(RULES).unwrap();
+    let mut state = State::default();
+    assert_eq!(rules.len(), 3);
+
+    // Not in game yet
+    assert!(rules.evaluate(&chat("Player", "gg all"), &state).is_empty());
+
+    state.ui_state = UIState::InGame;
+    assert_eq!(
+        rules.evaluate(&chat("Player;quit", "gg all"), &state),
+        vec![Action::Command(String::from("say gg all from Playerquit"))]
+    );
+    // Debounced
+    assert!(rules.evaluate(&chat("Player", "gg all"), &state).is_empty());
+
+    assert!(rules.evaluate(&damage(90), &state).is_empty());
+    assert_eq!(
+        rules.evaluate(&damage(91), &state),
+        vec![Action::Webhook {
+            url: String::from("http://localhost/hook"),
+            body: Some(String::from("Enemy 91")),
+        }]
+    );
+
+    let event = Event::MapChange(String::from("de_mirage"));
+    state.update(&event);
+    let actions = rules.evaluate(&event, &state);
+    assert_eq!(
+        actions,
+        vec![
+            Action::State(StateAction::Enable),
+            Action::Command(String::from("echo round 0 on de_mirage")),
+        ]
+    );
+    assert!(StateAction::Enable.apply(&mut state));
+    assert!(!StateAction::Enable.apply(&mut state));
+}
+
+#[test]
+fn rejects_invalid_rules() {
+    assert!(
+        Rules::parse("[[rule]]\nname = \"x\"\ntrigger = { event = \"nope\" }\nactions = []")
+            .is_err()
+    );
+    assert!(Rules::parse(
+        "[[rule]]\nname = \"x\"\ntrigger = { event = \"chat\", text = \"(\" }\nactions = []"
+    )
+    .is_err());
+}
+
+#[test]
+fn escapes_webhook_bodies() {
+    let mut rules = Rules::parse(
+        r#"
+[[rule]]
+name = "chat"
+trigger = { event = "chat" }
+actions = [{ webhook = { url = "http://localhost/hook", body = '{"sender": "{sender}", "text": "{text}"}' } }]
+"#,
+    )
+    .unwrap();
+
+    let text = r#"hi", "admin": true, "x": "{y}\"#;
+    let body = match &rules.evaluate(&chat("Player \"1\"", text), &State::default())[..] {
+        [Action::Webhook {
+            body: Some(body), ..
+        }] => body.clone(),
+        actions => panic!("unexpected actions {:?}", actions),
+    };
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({ "sender": "Player \"1\"", "text": text })
+    );
+}
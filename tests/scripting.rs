@@ -0,0 +1,58 @@
+#![cfg(feature = "scripting")]
+
+mod common;
+
+use std::time::Duration;
+
+use csgo_netcon::listener::Context;
+use csgo_netcon::scripting::ScriptListener;
+use csgo_netcon::types::{Event, State};
+use csgo_netcon::{Listener, NetconClient};
+
+use common::MockServer;
+
+#[tokio::test]
+async fn runs_and_reloads_scripts() {
+    let path = std::env::temp_dir().join(format!("netcon-script-{}.rhai", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"
+        fn on_load() { this.count = 0; }
+        fn on_event(event, state) {
+            if event.type == "MapChange" {
+                this.count += 1;
+                send(`echo ${event.data} ${this.count} ${state.round}`);
+            }
+        }
+        "#,
+    )
+    .unwrap();
+
+    let server = MockServer::start().await;
+    let client = NetconClient::connect(server.addr()).await.unwrap();
+    server.wait_for_connection().await;
+    let ctx = Context::new(Some(client.writer()));
+
+    let mut script = ScriptListener::load(&path).unwrap();
+    let state = State {
+        round: 3,
+        ..Default::default()
+    };
+    script.on_state_change(&ctx, &state).await;
+    script
+        .on_event(&ctx, &Event::MapChange(String::from("de_nuke")))
+        .await;
+    assert!(server.wait_for_command("echo de_nuke 1 3").await);
+
+    // Wait for the reload check and a newer modified time
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    std::fs::write(
+        &path,
+        r#"fn on_event(event, state) { send(`echo reloaded ${this.count}`); }"#,
+    )
+    .unwrap();
+    script.on_event(&ctx, &Event::EnterBuyPeriod).await;
+    assert!(server.wait_for_command("echo reloaded 1").await);
+
+    std::fs::remove_file(&path).unwrap();
+}
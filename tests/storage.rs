@@ -0,0 +1,199 @@
+#![cfg(feature = "sqlite")]
+
+use csgo_netcon::listener::Context;
+use csgo_netcon::rules::StateAction;
+use csgo_netcon::storage::{MatchFilter, MatchInfo, Storage, StorageListener};
+use csgo_netcon::types::RoundRecord;
+use csgo_netcon::types::{Damage, DamageDirection, Event, State};
+use csgo_netcon::Listener;
+
+#[tokio::test]
+async fn records_matches() {
+    let path = std::env::temp_dir().join(format!("netcon-storage-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let ctx = Context::new(None);
+    let mut listener = StorageListener::new(Storage::open(&path).unwrap());
+    let mut state = State::default();
+
+    let events = [
+        Event::MapChange(String::from("de_mirage")),
+        Event::EnterBuyPeriod,
+        Event::Damage(Damage {
+            direction: DamageDirection::Given,
+            target: String::from("Enemy"),
+            amount: 64,
+            hits: 2,
+        }),
+        Event::EnterBuyPeriod,
+        Event::Damage(Damage {
+            direction: DamageDirection::Taken,
+            target: String::from("Enemy"),
+            amount: 100,
+            hits: 1,
+        }),
+        // The block is printed again at the end of the round
+        Event::Damage(Damage {
+            direction: DamageDirection::Taken,
+            target: String::from("Enemy"),
+            amount: 100,
+            hits: 1,
+        }),
+    ];
+    for event in &events {
+        state.update(event);
+        listener.on_event(&ctx, event).await;
+        listener.on_state_change(&ctx, &state).await;
+    }
+    listener.on_shutdown(&ctx).await;
+    drop(listener);
+
+    let storage = Storage::open(&path).unwrap();
+    let conn = storage.connection();
+    let (map, ended): (String, Option<i64>) = conn
+        .query_row("SELECT map, ended_at FROM matches", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!(map, "de_mirage");
+    assert!(ended.is_some());
+
+    let mut statement = conn
+        .prepare("SELECT number, damage_given, damage_taken FROM rounds ORDER BY number")
+        .unwrap();
+    let rounds: Vec<(u8, u64, u64)> = statement
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(rounds, vec![(1, 64, 0), (2, 0, 100)]);
+
+    let damage: (i64, i64) = conn
+        .query_row("SELECT COUNT(*), SUM(amount) FROM damage", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!(damage, (2, 164));
+
+    drop(statement);
+    drop(storage);
+    std::fs::remove_file(&path).unwrap();
+}
+
+async fn play(listener: &mut StorageListener, ctx: &Context, state: &mut State, event: Event) {
+    state.update(&event);
+    listener.on_event(ctx, &event).await;
+    listener.on_state_change(ctx, state).await;
+}
+
+fn damage_given(amount: u8) -> Event {
+    Event::Damage(Damage {
+        direction: DamageDirection::Given,
+        target: String::from("Enemy"),
+        amount,
+        hits: 1,
+    })
+}
+
+#[tokio::test]
+async fn starts_a_new_match_on_start() {
+    let path = std::env::temp_dir().join(format!("netcon-restart-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let ctx = Context::new(None);
+    let mut listener = StorageListener::new(Storage::open(&path).unwrap());
+    let mut state = State::default();
+
+    let map = Event::MapChange(String::from("de_nuke"));
+    play(&mut listener, &ctx, &mut state, map).await;
+    for amount in [10, 20] {
+        play(&mut listener, &ctx, &mut state, Event::EnterBuyPeriod).await;
+        play(&mut listener, &ctx, &mut state, damage_given(amount)).await;
+    }
+    // The start command after warmup
+    StateAction::Start.apply(&mut state);
+    listener.on_state_change(&ctx, &state).await;
+    for amount in [30, 40] {
+        play(&mut listener, &ctx, &mut state, Event::EnterBuyPeriod).await;
+        play(&mut listener, &ctx, &mut state, damage_given(amount)).await;
+    }
+    listener.on_shutdown(&ctx).await;
+    drop(listener);
+
+    let storage = Storage::open(&path).unwrap();
+    let mut statement = storage
+        .connection()
+        .prepare("SELECT match_id, number, damage_given FROM rounds ORDER BY match_id, number")
+        .unwrap();
+    let rounds: Vec<(i64, u8, u64)> = statement
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(rounds, vec![(1, 1, 10), (1, 2, 20), (2, 1, 30), (2, 2, 40)]);
+
+    drop(statement);
+    drop(storage);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn filters_match_history() {
+    let storage = Storage::open_in_memory().unwrap();
+    for (map, mode, damage) in [
+        ("de_mirage", "Competitive", 80),
+        ("de_mirage", "Casual", 200),
+        ("de_inferno", "Competitive", 120),
+    ] {
+        let id = storage.begin_match(map, 0).unwrap();
+        storage
+            .update_match(
+                id,
+                &MatchInfo {
+                    game_mode: mode.to_string(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        for number in 1..=2 {
+            let mut round = RoundRecord::new(number);
+            round.record_damage(&Damage {
+                direction: DamageDirection::Given,
+                target: String::from("Enemy"),
+                amount: damage / 2,
+                hits: 1,
+            });
+            storage.record_round(id, &round).unwrap();
+        }
+        storage.end_match(id, 60_000).unwrap();
+    }
+
+    let filter = MatchFilter {
+        map: Some(String::from("de_mirage")),
+        mode: Some(String::from("competitive")),
+        limit: None,
+    };
+    let matches = storage.matches(&filter).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].rounds, 2);
+    assert_eq!(matches[0].damage_given, 80);
+    assert_eq!(matches[0].adr, 40.0);
+
+    let stats = storage
+        .stats(&MatchFilter {
+            mode: Some(String::from("Competitive")),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(stats.matches, 2);
+    assert_eq!(stats.rounds, 4);
+    assert_eq!(stats.adr, 50.0);
+
+    let recent = storage
+        .matches(&MatchFilter {
+            limit: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(recent[0].map, "de_inferno");
+}